@@ -0,0 +1,67 @@
+use glam::{Quat, Vec3};
+use std::sync::Arc;
+use trace_rs::{
+    bvh_node::BvhNode,
+    camera::Camera,
+    environment::Environment,
+    hittable::Hittable,
+    hittable_list::HittableList,
+    material::Material,
+    quad::{cuboid, Quad},
+};
+
+fn main() {
+    let mut world: HittableList<Arc<dyn Hittable + Send + Sync>> = HittableList::new();
+    let red = Material::Lambertian(Vec3::new(0.65, 0.05, 0.05).into());
+    let white = Material::Lambertian(Vec3::splat(0.73).into());
+    let green = Material::Lambertian(Vec3::new(0.12, 0.45, 0.15).into());
+    let light = Material::DiffuseLight {
+        color: Vec3::ONE,
+        intensity: 15.,
+        one_sided: true,
+    };
+
+    let (x, y, z) = (Vec3::X * 555., Vec3::Y * 555., Vec3::Z * 555.);
+    world.add(Arc::new(Quad::new(x, z, y, green)));
+    world.add(Arc::new(Quad::new(Vec3::ZERO, y, z, red)));
+    world.add(Arc::new(Quad::new(Vec3::ZERO, z, x, white.clone())));
+    world.add(Arc::new(Quad::new(y, x, z, white.clone())));
+    world.add(Arc::new(Quad::new(z, y, x, white.clone())));
+    world.add(Arc::new(Quad::new(
+        Vec3::new(213., 554., 227.),
+        Vec3::X * 130.,
+        Vec3::Z * 105.,
+        light,
+    )));
+
+    world.add(Arc::new(cuboid(
+        Vec3::new(265., 0., 295.),
+        Vec3::new(430., 330., 460.),
+        Quat::from_rotation_y(15_f32.to_radians()),
+        white.clone(),
+    )));
+    world.add(Arc::new(cuboid(
+        Vec3::new(130., 0., 65.),
+        Vec3::new(295., 165., 230.),
+        Quat::from_rotation_y(-18_f32.to_radians()),
+        white,
+    )));
+
+    let mut cam = Camera::new();
+    cam.aspect_ratio = 1.;
+    cam.image_width = 600;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.environment = Environment::Black;
+
+    cam.vfov = 40.;
+    cam.lookfrom = Vec3::new(278., 278., -800.);
+    cam.lookat = Vec3::new(278., 278., 0.);
+    cam.vup = Vec3::Y;
+
+    cam.defocus_angle = 0.;
+    cam.focus_dist = 10.;
+
+    let bvh = BvhNode::new(world);
+    cam.render(&bvh).save("image.ppm").unwrap();
+}
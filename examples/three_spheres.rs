@@ -3,36 +3,20 @@ use trace_rs::{camera::Camera, hittable_list::HittableList, material::Material,
 
 fn main() {
     let mut world: HittableList<Sphere> = HittableList::new();
-    let ground_material = Material::Lambertian(Vec3::new(0.8, 0.8, 0.0));
-    let center_material = Material::Lambertian(Vec3::new(0.1, 0.2, 0.5));
+    let ground_material = Material::Lambertian(Vec3::new(0.8, 0.8, 0.0).into());
+    let center_material = Material::Lambertian(Vec3::new(0.1, 0.2, 0.5).into());
     let left_material = Material::Dieletric(1.5);
     let bubble_material = Material::Dieletric(1.0 / 1.5);
-    let right_material = Material::Metal(Vec3::new(0.8, 0.6, 0.2), 1.0);
-    world.add(Sphere {
-        center: Vec3::NEG_Z * 1.2,
-        radius: 0.5,
-        material: center_material,
-    });
-    world.add(Sphere {
-        center: Vec3::new(0., -100.5, -1.),
-        radius: 100.,
-        material: ground_material,
-    });
-    world.add(Sphere {
-        center: Vec3::NEG_Z + Vec3::NEG_X,
-        radius: 0.5,
-        material: left_material,
-    });
-    world.add(Sphere {
-        center: Vec3::NEG_Z + Vec3::NEG_X,
-        radius: 0.4,
-        material: bubble_material,
-    });
-    world.add(Sphere {
-        center: Vec3::NEG_Z + Vec3::X,
-        radius: 0.5,
-        material: right_material,
-    });
+    let right_material = Material::Metal(Vec3::new(0.8, 0.6, 0.2).into(), 1.0.into());
+    world.add(Sphere::new(Vec3::NEG_Z * 1.2, 0.5, center_material));
+    world.add(Sphere::new(
+        Vec3::new(0., -100.5, -1.),
+        100.,
+        ground_material,
+    ));
+    world.add(Sphere::new(Vec3::NEG_Z + Vec3::NEG_X, 0.5, left_material));
+    world.add(Sphere::new(Vec3::NEG_Z + Vec3::NEG_X, 0.4, bubble_material));
+    world.add(Sphere::new(Vec3::NEG_Z + Vec3::X, 0.5, right_material));
 
     let mut cam = Camera::new();
     cam.aspect_ratio = 16. / 9.;
@@ -48,5 +32,5 @@ fn main() {
     cam.defocus_angle = 10.0;
     cam.focus_dist = 3.4;
 
-    cam.render(&world);
+    cam.render(&world).save("image.ppm").unwrap();
 }
@@ -7,18 +7,10 @@ fn main() {
     let r = (PI / 4.).cos();
 
     let mut world: HittableList<Sphere> = HittableList::new();
-    let left_material = Material::Lambertian(Vec3::Z);
-    let right_material = Material::Lambertian(Vec3::X);
-    world.add(Sphere {
-        center: Vec3::new(-r, 0., -1.),
-        radius: r,
-        material: left_material,
-    });
-    world.add(Sphere {
-        center: Vec3::new(r, 0., -1.),
-        radius: r,
-        material: right_material,
-    });
+    let left_material = Material::Lambertian(Vec3::Z.into());
+    let right_material = Material::Lambertian(Vec3::X.into());
+    world.add(Sphere::new(Vec3::new(-r, 0., -1.), r, left_material));
+    world.add(Sphere::new(Vec3::new(r, 0., -1.), r, right_material));
 
     let mut cam = Camera::new();
     cam.aspect_ratio = 16. / 9.;
@@ -27,5 +19,5 @@ fn main() {
     cam.max_depth = 10;
     cam.vfov = 90.;
 
-    cam.render(&world);
+    cam.render(&world).save("image.ppm").unwrap();
 }
@@ -5,18 +5,21 @@ use trace_rs::{
     camera::Camera,
     hittable_list::HittableList,
     material::Material,
+    random,
     sphere::Sphere,
     vector::{random_vec3, random_vec3_in},
 };
 
+const SEED: u64 = 2024;
+
 fn main() {
     let mut world: HittableList<Sphere> = HittableList::new();
 
-    let ground_material = Material::Lambertian(Vec3::splat(0.5));
+    let ground_material = Material::Lambertian(Vec3::splat(0.5).into());
 
     world.add(Sphere::new(Vec3::NEG_Y * 1000., 1000., ground_material));
 
-    let mut rng = rand::thread_rng();
+    let mut rng = random::seeded_rng(SEED);
     (-11..11).cartesian_product(-11..11).for_each(|(a, b)| {
         let choose_mat = rng.gen::<f32>();
         let center = Vec3::new(
@@ -26,10 +29,14 @@ fn main() {
         );
 
         if (center - Vec3::new(4., 0.2, 0.)).length() > 0.9 {
-            let mut material = Material::Lambertian(random_vec3() * random_vec3());
+            let mut material =
+                Material::Lambertian((random_vec3(&mut rng) * random_vec3(&mut rng)).into());
 
             if (0.8..0.95).contains(&choose_mat) {
-                material = Material::Metal(random_vec3_in(0.5..1.0), rng.gen_range(0.0..0.5));
+                material = Material::Metal(
+                    random_vec3_in(&mut rng, 0.5..1.0).into(),
+                    rng.gen_range(0.0_f32..0.5).into(),
+                );
             } else if choose_mat > 0.95 {
                 material = Material::Dieletric(1.5);
             };
@@ -46,10 +53,10 @@ fn main() {
     let mat1 = Material::Dieletric(1.5);
     world.add(Sphere::new(Vec3::Y, 1., mat1));
 
-    let mat2 = Material::Lambertian(Vec3::new(0.4, 0.2, 0.1));
+    let mat2 = Material::Lambertian(Vec3::new(0.4, 0.2, 0.1).into());
     world.add(Sphere::new(Vec3::Y + Vec3::NEG_X * 4., 1., mat2));
 
-    let mat3 = Material::Metal(Vec3::new(0.7, 0.6, 0.5), 0.);
+    let mat3 = Material::Metal(Vec3::new(0.7, 0.6, 0.5).into(), 0.0.into());
     world.add(Sphere::new(Vec3::Y + Vec3::X * 4., 1., mat3));
 
     let mut cam = Camera::new();
@@ -65,6 +72,7 @@ fn main() {
 
     cam.defocus_angle = 0.6;
     cam.focus_dist = 10.0;
+    cam.seed = SEED;
 
-    cam.render(&world);
+    cam.render(&world).save("image.ppm").unwrap();
 }
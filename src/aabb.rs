@@ -2,7 +2,11 @@ use std::ops::{Index, Range};
 
 use glam::Vec3;
 
-use crate::ray::Ray;
+use crate::{ray::Ray, stats};
+
+const PAD: f32 = 1e-4;
+// 1 + 2γ₃, bounding the error of the three operations behind each distance.
+const FAR_ROUNDING: f32 = 1. + 2. * (3. * f32::EPSILON / 2.) / (1. - 3. * f32::EPSILON / 2.);
 
 #[derive(Clone, Debug, Default)]
 pub struct Aabb {
@@ -13,7 +17,7 @@ pub struct Aabb {
 
 impl Aabb {
     pub fn new(x: Range<f32>, y: Range<f32>, z: Range<f32>) -> Self {
-        Self { x, y, z }
+        Self { x, y, z }.padded()
     }
 
     pub fn from_points(a: Vec3, b: Vec3) -> Self {
@@ -21,7 +25,7 @@ impl Aabb {
         let y = if a.y <= b.y { a.y..b.y } else { b.y..a.y };
         let z = if a.z <= b.z { a.z..b.z } else { b.z..a.z };
 
-        Self { x, y, z }
+        Self { x, y, z }.padded()
     }
 
     pub fn from_boxes(box0: Self, box1: &Self) -> Self {
@@ -32,6 +36,24 @@ impl Aabb {
         Self { x, y, z }
     }
 
+    // Flat primitives such as quads have no extent along their normal, and
+    // the slab test never reports a hit for an empty interval.
+    fn padded(self) -> Self {
+        let pad = |r: Range<f32>| {
+            if r.end - r.start < PAD {
+                let mid = 0.5 * (r.start + r.end);
+                mid - 0.5 * PAD..mid + 0.5 * PAD
+            } else {
+                r
+            }
+        };
+        Self {
+            x: pad(self.x),
+            y: pad(self.y),
+            z: pad(self.z),
+        }
+    }
+
     fn merge_intervals(a: Range<f32>, b: &Range<f32>) -> Range<f32> {
         a.start.min(b.start)..a.end.max(b.end)
     }
@@ -55,9 +77,14 @@ impl Aabb {
     }
 
     pub fn hit(&self, ray: &Ray, interval: Range<f32>) -> bool {
+        stats::count(|s| s.aabb_tests += 1);
         let ray_orig = ray.origin;
         let ray_dir = ray.direction;
 
+        // The interval narrows slab by slab, so the ray has to be inside all
+        // three at once.
+        let mut min = interval.start;
+        let mut max = interval.end;
         for axis in 0..3 {
             let ax = &self[axis];
             let adinv = 1.0 / ray_dir[axis];
@@ -65,23 +92,15 @@ impl Aabb {
             let t0 = (ax.start - ray_orig[axis]) * adinv;
             let t1 = (ax.end - ray_orig[axis]) * adinv;
 
-            let mut min = interval.start;
-            let mut max = interval.end;
-
-            if t0 < t1 {
-                if t0 > min {
-                    min = t0
-                };
-                if t1 < max {
-                    max = t1
-                };
-            } else {
-                if t1 > min {
-                    min = t1
-                };
-                if t0 < max {
-                    max = t0
-                };
+            let (near, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            // Rounding in the slab distances would otherwise let rays through
+            // an edge on the boundary between two boxes miss both.
+            let far = far * FAR_ROUNDING;
+            if near > min {
+                min = near;
+            }
+            if far < max {
+                max = far;
             }
 
             if max <= min {
@@ -0,0 +1,66 @@
+use crate::tone_map::luminance;
+use glam::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveSampling {
+    pub min_samples: usize,
+    pub threshold: f32,
+}
+
+impl AdaptiveSampling {
+    pub fn new(min_samples: usize, threshold: f32) -> Self {
+        Self {
+            min_samples,
+            threshold,
+        }
+    }
+
+    pub fn is_converged(&self, estimate: &PixelEstimate) -> bool {
+        estimate.count as usize >= self.min_samples.max(2)
+            && estimate.relative_error() <= self.threshold
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PixelEstimate {
+    pub sum: Vec3,
+    pub luminance_sum: f32,
+    pub luminance_sq_sum: f32,
+    pub count: u32,
+}
+
+impl PixelEstimate {
+    pub fn add(&mut self, color: Vec3) {
+        let l = luminance(color);
+        self.sum += color;
+        self.luminance_sum += l;
+        self.luminance_sq_sum += l * l;
+        self.count += 1;
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.sum += other.sum;
+        self.luminance_sum += other.luminance_sum;
+        self.luminance_sq_sum += other.luminance_sq_sum;
+        self.count += other.count;
+    }
+
+    pub fn mean(&self) -> Vec3 {
+        if self.count == 0 {
+            return Vec3::ZERO;
+        }
+        self.sum / self.count as f32
+    }
+
+    // Standard error of the mean luminance relative to the mean itself, with
+    // a floor on the mean so near-black pixels don't sample forever.
+    pub fn relative_error(&self) -> f32 {
+        if self.count < 2 {
+            return f32::INFINITY;
+        }
+        let n = self.count as f32;
+        let mean = self.luminance_sum / n;
+        let variance = ((self.luminance_sq_sum - mean * self.luminance_sum) / (n - 1.)).max(0.);
+        (variance / n).sqrt() / mean.max(0.01)
+    }
+}
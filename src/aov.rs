@@ -0,0 +1,99 @@
+use glam::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aov {
+    Normal,
+    Albedo,
+    Depth,
+    Position,
+    ObjectId,
+}
+
+// What a camera ray saw at its first intersection.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SurfaceSample {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub albedo: Vec3,
+    pub depth: f32,
+    pub object_id: u32,
+}
+
+// Normals and albedo are averaged over every sample so edges against the
+// background stay antialiased; depth and position only over samples that
+// hit something. The object ID is the first one a sample reported.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AovAccumulator {
+    pub normal_sum: Vec3,
+    pub albedo_sum: Vec3,
+    pub position_sum: Vec3,
+    pub depth_sum: f32,
+    pub hits: u32,
+    pub object_id: u32,
+}
+
+impl AovAccumulator {
+    pub fn add(&mut self, surface: Option<&SurfaceSample>) {
+        let Some(surface) = surface else {
+            return;
+        };
+        self.normal_sum += surface.normal;
+        self.albedo_sum += surface.albedo;
+        self.position_sum += surface.position;
+        self.depth_sum += surface.depth;
+        self.hits += 1;
+        if self.object_id == 0 {
+            self.object_id = surface.object_id;
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.normal_sum += other.normal_sum;
+        self.albedo_sum += other.albedo_sum;
+        self.position_sum += other.position_sum;
+        self.depth_sum += other.depth_sum;
+        self.hits += other.hits;
+        if self.object_id == 0 {
+            self.object_id = other.object_id;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Aovs {
+    pub normal: Vec<Vec3>,
+    pub albedo: Vec<Vec3>,
+    pub depth: Vec<f32>,
+    pub position: Vec<Vec3>,
+    pub object_id: Vec<u32>,
+}
+
+impl Aovs {
+    pub fn resolve(accumulators: &[(AovAccumulator, u32)]) -> Self {
+        let mut aovs = Self::default();
+        for (acc, samples) in accumulators {
+            let per_sample = 1. / (*samples).max(1) as f32;
+            let per_hit = 1. / acc.hits.max(1) as f32;
+            aovs.normal.push(acc.normal_sum * per_sample);
+            aovs.albedo.push(acc.albedo_sum * per_sample);
+            aovs.depth.push(acc.depth_sum * per_hit);
+            aovs.position.push(acc.position_sum * per_hit);
+            aovs.object_id.push(acc.object_id);
+        }
+        aovs
+    }
+
+    pub fn colors(&self, aov: Aov) -> Vec<Vec3> {
+        match aov {
+            Aov::Normal => self.normal.clone(),
+            Aov::Albedo => self.albedo.clone(),
+            Aov::Depth => self.depth.iter().map(|&d| Vec3::splat(d)).collect(),
+            Aov::Position => self.position.clone(),
+            Aov::ObjectId => self
+                .object_id
+                .iter()
+                .map(|&id| Vec3::splat(id as f32))
+                .collect(),
+        }
+    }
+}
@@ -3,19 +3,30 @@ use crate::{
     hittable::{HitRecord, Hittable},
     hittable_list::HittableList,
     ray::Ray,
+    stats,
 };
-use std::ops::Range;
+use std::{ops::Range, time::Instant};
 
 pub struct BvhNode {
-    pub left: Box<dyn Hittable + Sync>,
-    pub right: Box<dyn Hittable + Sync>,
+    pub left: Box<dyn Hittable + Send + Sync>,
+    pub right: Box<dyn Hittable + Send + Sync>,
     pub bbox: Aabb,
 }
 
 impl BvhNode {
     pub fn new<T>(list: HittableList<T>) -> Self
     where
-        T: Hittable + 'static + Clone + Sync,
+        T: Hittable + 'static + Clone + Send + Sync,
+    {
+        let start = Instant::now();
+        let node = Self::build(list);
+        stats::record(|s| s.build_time += start.elapsed());
+        node
+    }
+
+    fn build<T>(list: HittableList<T>) -> Self
+    where
+        T: Hittable + 'static + Clone + Send + Sync,
     {
         let mut objects = list.objects.clone();
 
@@ -32,16 +43,16 @@ impl BvhNode {
 
         let mid = objects.len() / 2;
         for o in &objects[..mid] {
-            left.add(o.clone());
+            left.push(o.clone());
         }
         for o in &objects[mid..] {
-            right.add(o.clone());
+            right.push(o.clone());
         }
         let bbox = Aabb::from_boxes(left.bounding_box().clone(), right.bounding_box());
         if objects.len() >= 32 {
             Self {
-                left: Box::new(Self::new(left)),
-                right: Box::new(Self::new(right)),
+                left: Box::new(Self::build(left)),
+                right: Box::new(Self::build(right)),
                 bbox,
             }
         } else {
@@ -55,7 +66,7 @@ impl BvhNode {
 }
 
 impl Hittable for BvhNode {
-    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord<'_>> {
         if !self.bbox.hit(ray, interval.clone()) {
             return None;
         }
@@ -85,4 +96,9 @@ impl Hittable for BvhNode {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn set_object_id(&mut self, id: u32) {
+        self.left.set_object_id(id);
+        self.right.set_object_id(id);
+    }
 }
@@ -1,10 +1,33 @@
-use crate::{color, hittable::Hittable, ray::Ray, vector::random_in_unit_disk};
-use glam::Vec3;
-use indicatif::ParallelProgressIterator;
-use itertools::Itertools;
-use rand::Rng;
+use crate::{
+    adaptive::{AdaptiveSampling, PixelEstimate},
+    aov::AovAccumulator,
+    checkpoint::{Checkpoint, Checkpointing},
+    control::RenderControl,
+    crop::Crop,
+    environment::Environment,
+    film::{Film, FilmTile},
+    filter::Filter,
+    framebuffer::Framebuffer,
+    hittable::Hittable,
+    progress::{RenderObserver, RenderProgress, TerminalProgress},
+    progressive::Progressive,
+    ray::Ray,
+    sampler::{Sampler, SamplerKind},
+    stats::{self, RenderStats},
+    vector::sample_unit_disk,
+};
+use glam::{Vec2, Vec3};
 use rayon::prelude::*;
-use std::fs;
+use std::{
+    io,
+    ops::Range,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+const DEFAULT_TILE_SIZE: u32 = 32;
+const DEFAULT_PASS_SAMPLES: usize = 4;
 
 #[derive(Clone, Debug, Default)]
 pub struct Camera {
@@ -21,14 +44,33 @@ pub struct Camera {
     pub defocus_angle: f32,
     pub focus_dist: f32,
 
+    pub environment: Environment,
+
+    pub seed: u64,
+    pub sampler: SamplerKind,
+    pub adaptive: Option<AdaptiveSampling>,
+    pub filter: Filter,
+    pub aovs: bool,
+    pub crop: Option<Crop>,
+    pub transparent_background: bool,
+
+    pub tile_size: u32,
+    pub checkpoint: Option<Checkpointing>,
+    pub progressive: Option<Progressive>,
+    pub control: RenderControl,
+    pub observer: Option<Arc<dyn RenderObserver>>,
+
     image_height: u32,
     center: Vec3,
     pixel00_loc: Vec3,
     pixel_delta_u: Vec3,
     pixel_delta_v: Vec3,
-    pixel_samples_scale: f32,
+    pixel_spread: f32,
     defocus_disk_u: Vec3,
     defocus_disk_v: Vec3,
+    started: Option<Instant>,
+    deadline: Option<Instant>,
+    stats: RenderStats,
 }
 
 impl Camera {
@@ -36,55 +78,398 @@ impl Camera {
         Default::default()
     }
 
-    pub fn render<T>(&mut self, world: &T)
+    pub fn render<T>(&mut self, world: &T) -> Framebuffer
+    where
+        T: Hittable + 'static + Sync,
+    {
+        if let Err(err) = self.initialize() {
+            panic!("{err}");
+        }
+
+        let film = Film::new(self.image_width, self.image_height);
+        let film = self.render_with_stats(world, film);
+        self.to_framebuffer(&film)
+    }
+
+    // Continues a checkpointed render up to `samples_per_pixel`. With
+    // `SamplerKind::Stratified` the strata depend on the sample count, so a
+    // resume to a higher count stays unbiased but isn't sample-for-sample the
+    // same as rendering that count directly.
+    pub fn resume<T>(&mut self, world: &T, checkpoint: impl AsRef<Path>) -> io::Result<Framebuffer>
     where
         T: Hittable + 'static + Sync,
     {
-        self.initialize();
-
-        let pixels = (0..self.image_height)
-            .cartesian_product(0..self.image_width)
-            .collect::<Vec<(u32, u32)>>()
-            .into_par_iter()
-            .progress_count(self.image_width as u64 * self.image_height as u64)
-            .map(|(y, x)| {
-                let mut pixel_color = Vec3::ZERO;
-                for _ in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(x, y);
-                    pixel_color += ray.color(world, self.max_depth);
+        self.initialize()?;
+
+        let checkpoint = Checkpoint::load(checkpoint)?;
+        let mismatch = if checkpoint.film.width != self.image_width
+            || checkpoint.film.height != self.image_height
+        {
+            Some("resolution")
+        } else if checkpoint.seed != self.seed {
+            Some("seed")
+        } else if checkpoint.sampler != self.sampler {
+            Some("sampler")
+        } else if checkpoint.filter != self.filter {
+            Some("filter")
+        } else if checkpoint.region != self.render_region() {
+            Some("crop")
+        } else {
+            None
+        };
+        if let Some(setting) = mismatch {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("checkpoint was rendered with a different {setting}"),
+            ));
+        }
+
+        let film = self.render_with_stats(world, checkpoint.film);
+        Ok(self.to_framebuffer(&film))
+    }
+
+    // Timings are always filled in; the counters stay zero unless the crate
+    // is built with the `stats` feature.
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
+    // Counters recorded on the calling thread since the last take, such as a
+    // BVH built just before rendering, are attributed to this render.
+    fn render_with_stats<T>(&mut self, world: &T, film: Film) -> Film
+    where
+        T: Hittable + 'static + Sync,
+    {
+        let mut stats = stats::take();
+        let film = self.render_passes(world, film, &mut stats);
+        stats.render_time = self.elapsed();
+        self.stats = stats;
+        film
+    }
+
+    // Renders always run in passes, so a render that is cancelled or runs
+    // out of time still covers the whole frame. Stop conditions are also
+    // checked between samples to stop promptly, which leaves pixel sample
+    // counts up to one pass apart, though never below one.
+    fn render_passes<T>(&self, world: &T, film: Film, stats: &mut RenderStats) -> Film
+    where
+        T: Hittable + 'static + Sync,
+    {
+        let samples_per_pass = self
+            .progressive
+            .as_ref()
+            .map_or(DEFAULT_PASS_SAMPLES, |progressive| {
+                progressive.samples_per_pass
+            });
+
+        // Without an observer the render keeps the terminal progress bar.
+        let terminal;
+        let observer: &dyn RenderObserver = match &self.observer {
+            Some(observer) => observer.as_ref(),
+            None => {
+                terminal = TerminalProgress::new();
+                &terminal
+            }
+        };
+
+        let step = samples_per_pass.max(1) as u32;
+        let total = self.samples_per_pixel as u32;
+        // A resumed film already has the passes below its least sampled pixel,
+        // which would only revisit every pixel to add nothing.
+        let (xs, ys) = self.render_region();
+        let start = film.min_samples(xs, ys).min(total) / step * step;
+        let mut state = PassState {
+            progress: RenderProgress {
+                passes: (total - start).div_ceil(step) as usize,
+                tiles: self.tiles().len(),
+                samples: film.samples(),
+                ..Default::default()
+            },
+            stats: RenderStats::default(),
+            last_checkpoint: Instant::now(),
+        };
+        observer.started(&state.progress);
+
+        let mut film = film;
+        let mut target = start;
+        let mut last_snapshot = Instant::now();
+        while target < total {
+            target = (target + step).min(total);
+            state.progress.pass += 1;
+            state.progress.tiles_finished = 0;
+            film = self.render_tiles(world, film, target, observer, &mut state);
+            state.progress.elapsed = self.elapsed();
+            observer.pass_finished(&state.progress);
+
+            let stop = self.should_stop()
+                || self
+                    .control
+                    .target_noise
+                    .is_some_and(|noise| film.mean_relative_error() <= noise);
+
+            if let Some(progressive) = &self.progressive {
+                if target == total
+                    || stop
+                    || last_snapshot.elapsed() >= progressive.snapshot_interval
+                {
+                    let path = &progressive.snapshot_path;
+                    if let Err(err) = progressive.writer.write(&self.to_framebuffer(&film), path) {
+                        observer.write_failed(path, &err);
+                    }
+                    last_snapshot = Instant::now();
                 }
-                pixel_color *= self.pixel_samples_scale;
-                color::to_ppm(pixel_color)
+            }
+
+            if let Some(checkpointing) = &self.checkpoint {
+                if target == total
+                    || stop
+                    || state.last_checkpoint.elapsed() >= checkpointing.interval
+                {
+                    self.write_checkpoint(film.clone(), &checkpointing.path, observer);
+                    state.last_checkpoint = Instant::now();
+                }
+            }
+
+            if stop {
+                break;
+            }
+        }
+
+        state.progress.elapsed = self.elapsed();
+        observer.finished(&state.progress);
+        stats.merge(&state.stats);
+        film
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.started.map(|s| s.elapsed()).unwrap_or_default()
+    }
+
+    fn should_stop(&self) -> bool {
+        self.control.is_cancelled() || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    fn render_tiles<T>(
+        &self,
+        world: &T,
+        base: Film,
+        target_samples: u32,
+        observer: &dyn RenderObserver,
+        state: &mut PassState,
+    ) -> Film
+    where
+        T: Hittable + 'static + Sync,
+    {
+        let tiles = self.tiles();
+        // Held while a checkpoint is written, so writes never overlap and a
+        // newer snapshot can't be overtaken by an older one.
+        let writing = Mutex::new(());
+        let results = Mutex::new(TileResults {
+            finished: vec![None; tiles.len()],
+            last_checkpoint: state.last_checkpoint,
+            progress: state.progress,
+            stats: RenderStats::default(),
+        });
+
+        tiles.par_iter().enumerate().for_each(|(i, (xs, ys))| {
+            // Drop anything this worker thread recorded outside of a tile.
+            stats::take();
+
+            let mut tile = FilmTile::covering(
+                xs.clone(),
+                ys.clone(),
+                self.image_width,
+                self.image_height,
+                self.filter,
+            );
+            let mut sampler = self.sampler.build(self.seed, self.samples_per_pixel);
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let prior = base.pixel(x, y).estimate;
+                    self.render_pixel(
+                        world,
+                        &mut tile,
+                        sampler.as_mut(),
+                        (x, y),
+                        prior,
+                        target_samples,
+                    );
+                }
+            }
+
+            let tile_stats = stats::take();
+            let mut results = results.lock().unwrap();
+            results.stats.merge(&tile_stats);
+            results.progress.tiles_finished += 1;
+            results.progress.samples += tile.samples();
+            results.progress.elapsed = self.elapsed();
+            observer.tile_finished(&results.progress);
+
+            results.finished[i] = Some(tile);
+            let Some(checkpointing) = &self.checkpoint else {
+                return;
+            };
+            if results.last_checkpoint.elapsed() < checkpointing.interval {
+                return;
+            }
+            // Other workers only wait for the snapshot, not for the write.
+            let Ok(_writing) = writing.try_lock() else {
+                return;
+            };
+            let film = results.assemble(&base);
+            results.last_checkpoint = Instant::now();
+            drop(results);
+            self.write_checkpoint(film, &checkpointing.path, observer);
+        });
+
+        let results = results.into_inner().unwrap();
+        state.progress = results.progress;
+        state.last_checkpoint = results.last_checkpoint;
+        state.stats.merge(&results.stats);
+        results.assemble(&base)
+    }
+
+    fn tiles(&self) -> Vec<(Range<u32>, Range<u32>)> {
+        let size = if self.tile_size == 0 {
+            DEFAULT_TILE_SIZE
+        } else {
+            self.tile_size
+        };
+        let (xs, ys) = self.render_region();
+
+        // Tiles follow the full image's grid even when cropped, so pixels are
+        // merged in the same order and come out bit-identical.
+        (0..self.image_height)
+            .step_by(size as usize)
+            .flat_map(|y| {
+                let xs = xs.clone();
+                let ys = y.max(ys.start)..(y + size).min(ys.end);
+                (0..self.image_width)
+                    .step_by(size as usize)
+                    .map(move |x| (x.max(xs.start)..(x + size).min(xs.end), ys.clone()))
             })
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        fs::write(
-            "image.ppm",
-            format!(
-                "P3\n{} {}\n255\n{pixels}\n",
-                self.image_width, self.image_height,
-            ),
+            .filter(|(xs, ys)| !xs.is_empty() && !ys.is_empty())
+            .collect()
+    }
+
+    // A crop also needs the pixels just outside it whose samples the filter
+    // spreads onto its edges.
+    fn render_region(&self) -> (Range<u32>, Range<u32>) {
+        let (width, height) = (self.image_width, self.image_height);
+        let Some(crop) = &self.crop else {
+            return (0..width, 0..height);
+        };
+        let (xs, ys) = crop.clamped(width, height);
+        let reach = self.filter.reach();
+        (
+            xs.start.saturating_sub(reach)..(xs.end + reach).min(width),
+            ys.start.saturating_sub(reach)..(ys.end + reach).min(height),
         )
-        .unwrap();
     }
 
-    fn get_ray(&self, x: u32, y: u32) -> Ray {
-        let mut rng = rand::thread_rng();
-        let x_offset = rng.gen::<f32>();
-        let y_offset = rng.gen::<f32>();
+    fn write_checkpoint(&self, film: Film, path: &Path, observer: &dyn RenderObserver) {
+        let checkpoint = Checkpoint {
+            seed: self.seed,
+            sampler: self.sampler,
+            filter: self.filter,
+            region: self.render_region(),
+            film,
+        };
+        if let Err(err) = checkpoint.save(path) {
+            observer.write_failed(path, &err);
+        }
+    }
+
+    fn to_framebuffer(&self, film: &Film) -> Framebuffer {
+        let cropped;
+        let film = match &self.crop {
+            Some(crop) => {
+                let (xs, ys) = crop.clamped(self.image_width, self.image_height);
+                cropped = if crop.full_frame {
+                    film.mask(xs, ys)
+                } else {
+                    film.crop(xs, ys)
+                };
+                &cropped
+            }
+            None => film,
+        };
+
+        let mut image = film.to_framebuffer();
+        if self.aovs {
+            image.aovs = Some(film.aovs());
+        }
+        if self.transparent_background {
+            image.alpha = Some(film.alpha());
+        }
+        image.metadata = self.metadata();
+        image
+    }
+
+    // Continues the pixel from the samples already in `prior` up to
+    // `target_samples`; only the new samples end up in the tile.
+    fn render_pixel<T>(
+        &self,
+        world: &T,
+        tile: &mut FilmTile,
+        sampler: &mut dyn Sampler,
+        (x, y): (u32, u32),
+        prior: PixelEstimate,
+        target_samples: u32,
+    ) where
+        T: Hittable + 'static + Sync,
+    {
+        let mut total = prior;
+        let mut added = PixelEstimate::default();
+        let mut aov = AovAccumulator::default();
+        for sample in prior.count..target_samples {
+            // Every pixel gets at least one sample before a stop is honoured,
+            // so a render stopped during its first pass still has no holes.
+            let stopped = total.count > 0 && self.should_stop();
+            if self.adaptive.is_some_and(|a| a.is_converged(&total)) || stopped {
+                break;
+            }
+
+            sampler.start_sample(x, y, sample);
+            stats::count(|s| s.camera_rays += 1);
+            let film_position = Vec2::new(x as f32, y as f32) + sampler.get_2d();
+            let ray = self.get_ray(film_position, sampler);
+            let path = ray.trace(
+                world,
+                &self.environment,
+                self.max_depth,
+                self.pixel_spread,
+                sampler,
+            );
+            // With a transparent background, camera rays that escape leave
+            // nothing behind, while the environment still lights everything
+            // they hit.
+            let (radiance, alpha) = match path.first_hit {
+                None if self.transparent_background => (Vec3::ZERO, 0.),
+                _ => (path.radiance, 1.),
+            };
+            total.add(radiance);
+            added.add(radiance);
+            aov.add(path.first_hit.as_ref());
+            tile.add_sample(film_position, radiance, alpha);
+        }
+        tile.set_samples(x, y, added, aov);
+    }
 
+    fn get_ray(&self, film_position: Vec2, sampler: &mut dyn Sampler) -> Ray {
+        // pixel00_loc is the center of the first pixel, while film positions
+        // put pixel centers at half-integer coordinates.
         let pixel_sample = self.pixel00_loc
-            + (x as f32 + x_offset) * self.pixel_delta_u
-            + (y as f32 + y_offset) * self.pixel_delta_v;
+            + (film_position.x - 0.5) * self.pixel_delta_u
+            + (film_position.y - 0.5) * self.pixel_delta_v;
 
         let origin = if self.defocus_angle <= 0. {
             self.center
         } else {
-            self.defocus_disk_sample()
+            self.defocus_disk_sample(sampler)
         };
         let direction = pixel_sample - origin;
-        let time = rng.gen::<f32>();
+        let time = sampler.get_1d();
 
         Ray {
             origin,
@@ -93,12 +478,28 @@ impl Camera {
         }
     }
 
-    fn initialize(&mut self) {
+    // Only fails for a crop that leaves none of the image.
+    fn initialize(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        self.started = Some(now);
+        self.deadline = self.control.time_budget.map(|budget| now + budget);
+
         self.image_height = ((self.image_width as f32 / self.aspect_ratio) as u32).max(1);
 
-        self.center = self.lookfrom;
+        if let Some(crop) = &self.crop {
+            let (xs, ys) = crop.clamped(self.image_width, self.image_height);
+            if xs.is_empty() || ys.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "crop x {:?}, y {:?} leaves no pixels of the {}x{} image",
+                        crop.x, crop.y, self.image_width, self.image_height
+                    ),
+                ));
+            }
+        }
 
-        self.pixel_samples_scale = (self.samples_per_pixel as f32).recip();
+        self.center = self.lookfrom;
 
         let theta = self.vfov.to_radians();
         let h = (theta / 2.).tan();
@@ -114,6 +515,7 @@ impl Camera {
 
         self.pixel_delta_u = viewport_u / self.image_width as f32;
         self.pixel_delta_v = viewport_v / self.image_height as f32;
+        self.pixel_spread = self.pixel_delta_u.length() / self.focus_dist;
 
         let viewport_upper_left =
             self.center - w * self.focus_dist - viewport_u / 2. - viewport_v / 2.;
@@ -124,10 +526,313 @@ impl Camera {
 
         self.defocus_disk_u = u * defocus_radius;
         self.defocus_disk_v = v * defocus_radius;
+        Ok(())
     }
 
-    fn defocus_disk_sample(&self) -> Vec3 {
-        let p = random_in_unit_disk();
+    fn metadata(&self) -> Vec<(String, String)> {
+        [
+            (
+                "Software",
+                format!("trace-rs {}", env!("CARGO_PKG_VERSION")),
+            ),
+            (
+                "Resolution",
+                format!("{}x{}", self.image_width, self.image_height),
+            ),
+            ("Samples per pixel", self.samples_per_pixel.to_string()),
+            ("Max depth", self.max_depth.to_string()),
+            ("Vertical FOV", self.vfov.to_string()),
+            ("Look from", self.lookfrom.to_string()),
+            ("Look at", self.lookat.to_string()),
+            ("View up", self.vup.to_string()),
+            ("Defocus angle", self.defocus_angle.to_string()),
+            ("Focus distance", self.focus_dist.to_string()),
+            ("Seed", self.seed.to_string()),
+            ("Sampler", format!("{:?}", self.sampler)),
+            ("Filter", format!("{:?}", self.filter)),
+            (
+                "Transparent background",
+                self.transparent_background.to_string(),
+            ),
+        ]
+        .into_iter()
+        .chain(self.crop.iter().map(|crop| {
+            let (xs, ys) = crop.clamped(self.image_width, self.image_height);
+            ("Crop", format!("x {xs:?}, y {ys:?}"))
+        }))
+        .chain(self.adaptive.iter().flat_map(|a| {
+            [
+                ("Adaptive min samples", a.min_samples.to_string()),
+                ("Adaptive threshold", a.threshold.to_string()),
+            ]
+        }))
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+    }
+
+    fn defocus_disk_sample(&self, sampler: &mut dyn Sampler) -> Vec3 {
+        let p = sample_unit_disk(sampler.get_2d());
         self.center + p.x * self.defocus_disk_u + p.y * self.defocus_disk_v
     }
 }
+
+// What carries over from one pass to the next.
+struct PassState {
+    progress: RenderProgress,
+    stats: RenderStats,
+    last_checkpoint: Instant,
+}
+
+struct TileResults {
+    finished: Vec<Option<FilmTile>>,
+    last_checkpoint: Instant,
+    progress: RenderProgress,
+    stats: RenderStats,
+}
+
+impl TileResults {
+    // Tiles overlap where the filter reaches past their edges, so they are
+    // merged in tile order rather than completion order to keep renders
+    // deterministic.
+    fn assemble(&self, base: &Film) -> Film {
+        let mut film = base.clone();
+        for tile in self.finished.iter().flatten() {
+            film.merge(tile);
+        }
+        film
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bvh_node::BvhNode, hittable_list::HittableList, material::Material,
+        progress::SilentProgress, sphere::Sphere,
+    };
+    use std::path::PathBuf;
+
+    fn world() -> BvhNode {
+        let mut world = HittableList::new();
+        world.add(Sphere::new(
+            Vec3::new(0., -100.5, -1.),
+            100.,
+            Material::Lambertian(Vec3::new(0.8, 0.8, 0.).into()),
+        ));
+        world.add(Sphere::new(
+            Vec3::NEG_Z,
+            0.5,
+            Material::Lambertian(Vec3::new(0.1, 0.2, 0.5).into()),
+        ));
+        world.add(Sphere::new(
+            Vec3::new(-1., 0., -1.),
+            0.5,
+            Material::Dieletric(1.5),
+        ));
+        world.add(Sphere::new(
+            Vec3::new(1., 0., -1.),
+            0.5,
+            Material::Metal(Vec3::new(0.8, 0.6, 0.2).into(), 0.3.into()),
+        ));
+        BvhNode::new(world)
+    }
+
+    fn camera() -> Camera {
+        let mut cam = Camera::new();
+        cam.aspect_ratio = 2.;
+        cam.image_width = 48;
+        cam.samples_per_pixel = 8;
+        cam.max_depth = 10;
+        cam.lookfrom = Vec3::new(0., 0.5, 1.);
+        cam.lookat = Vec3::NEG_Z;
+        cam.vup = Vec3::Y;
+        cam.vfov = 60.;
+        cam.focus_dist = 2.;
+        cam.seed = 42;
+        cam.observer = Some(Arc::new(SilentProgress));
+        cam
+    }
+
+    fn bits(image: &Framebuffer) -> Vec<u32> {
+        image
+            .pixels
+            .iter()
+            .flat_map(|c| c.to_array().map(f32::to_bits))
+            .collect()
+    }
+
+    fn render_with_threads(threads: usize) -> Framebuffer {
+        let world = world();
+        let mut cam = camera();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        pool.install(|| cam.render(&world))
+    }
+
+    #[test]
+    fn output_does_not_depend_on_thread_count() {
+        let single = render_with_threads(1);
+        let many = render_with_threads(4);
+        assert_eq!(bits(&single), bits(&many));
+        assert_eq!(single.sample_counts, many.sample_counts);
+        assert!(single.pixels.iter().any(|&c| c != Vec3::ZERO));
+    }
+
+    fn checkpoint_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("trace-rs-{name}.ckpt"))
+    }
+
+    #[test]
+    fn resume_matches_direct_render() {
+        let world = world();
+        for sampler in [
+            SamplerKind::Independent,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+        ] {
+            let path = checkpoint_path(&format!("resume-{sampler:?}"));
+            let mut cam = camera();
+            cam.sampler = sampler;
+            cam.samples_per_pixel = 4;
+            cam.checkpoint = Some(Checkpointing::new(&path, Duration::from_secs(3600)));
+            cam.render(&world);
+
+            let mut resumed = camera();
+            resumed.sampler = sampler;
+            resumed.samples_per_pixel = 12;
+            let resumed = resumed.resume(&world, &path).unwrap();
+            let _ = std::fs::remove_file(&path);
+
+            let mut direct = camera();
+            direct.sampler = sampler;
+            direct.samples_per_pixel = 12;
+            let direct = direct.render(&world);
+            assert_eq!(bits(&resumed), bits(&direct), "{sampler:?}");
+            assert_eq!(resumed.sample_counts, direct.sample_counts);
+        }
+    }
+
+    #[test]
+    fn resume_refuses_other_settings() {
+        let world = world();
+        let path = checkpoint_path("refuse");
+        let mut cam = camera();
+        cam.samples_per_pixel = 2;
+        cam.checkpoint = Some(Checkpointing::new(&path, Duration::from_secs(3600)));
+        cam.render(&world);
+
+        let mut filter = camera();
+        filter.filter = Filter::Tent { radius: 1. };
+        let mut seed = camera();
+        seed.seed += 1;
+        let mut crop = camera();
+        crop.crop = Some(Crop::new(0..8, 0..8));
+        for mut cam in [filter, seed, crop] {
+            let err = cam.resume(&world, &path).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[derive(Debug, Default)]
+    struct PassCounter(Mutex<usize>);
+
+    impl RenderObserver for PassCounter {
+        fn pass_finished(&self, _progress: &RenderProgress) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn resume_skips_finished_passes() {
+        let world = world();
+        let path = checkpoint_path("skip-passes");
+        let mut cam = camera();
+        cam.samples_per_pixel = 8;
+        cam.checkpoint = Some(Checkpointing::new(&path, Duration::from_secs(3600)));
+        cam.render(&world);
+
+        let counter = Arc::new(PassCounter::default());
+        let mut resumed = camera();
+        resumed.samples_per_pixel = 12;
+        resumed.observer = Some(counter.clone());
+        resumed.resume(&world, &path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(*counter.0.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn stopped_render_samples_every_pixel() {
+        let world = world();
+        let mut cam = camera();
+        cam.samples_per_pixel = 1000;
+        cam.control.time_budget = Some(Duration::ZERO);
+        let image = cam.render(&world);
+        assert!(image.sample_counts.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn reset_control_renders_again() {
+        let world = world();
+        let mut cam = camera();
+        cam.control.cancel();
+        let cancelled = cam.render(&world);
+        assert!(cancelled.sample_counts.iter().all(|&count| count == 1));
+
+        cam.control.reset();
+        let image = cam.render(&world);
+        assert!(image.sample_counts.iter().all(|&count| count == 8));
+    }
+
+    #[test]
+    fn resume_rejects_empty_crop() {
+        let mut cam = camera();
+        cam.crop = Some(Crop::new(60..70, 0..10));
+        let err = cam
+            .resume(&world(), checkpoint_path("empty-crop"))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn crop_matches_full_render() {
+        let world = world();
+        let filters = [
+            Filter::default(),
+            Filter::Gaussian {
+                radius: 1.5,
+                sigma: 0.5,
+            },
+        ];
+        for sampler in [
+            SamplerKind::Independent,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+        ] {
+            for filter in filters {
+                let mut cam = camera();
+                cam.sampler = sampler;
+                cam.filter = filter;
+                let full = cam.render(&world);
+                cam.crop = Some(Crop::new(10..30, 5..17));
+                let cropped = cam.render(&world);
+
+                assert_eq!((cropped.width, cropped.height), (20, 12));
+                for y in 0..12 {
+                    for x in 0..20 {
+                        let i = (y * 20 + x) as usize;
+                        let j = ((y + 5) * full.width + x + 10) as usize;
+                        assert_eq!(
+                            cropped.pixels[i].to_array().map(f32::to_bits),
+                            full.pixels[j].to_array().map(f32::to_bits),
+                            "{sampler:?} {filter:?} at {x}, {y}"
+                        );
+                        assert_eq!(cropped.sample_counts[i], full.sample_counts[j]);
+                    }
+                }
+            }
+        }
+    }
+}
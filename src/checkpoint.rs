@@ -0,0 +1,245 @@
+use crate::{
+    film::{Film, FilmPixel},
+    filter::Filter,
+    sampler::SamplerKind,
+};
+use glam::Vec3;
+use std::{
+    fs,
+    io::{self, Read},
+    ops::Range,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const MAGIC: &[u8; 4] = b"TRCK";
+const VERSION: u32 = 4;
+// Twenty f32 and three u32 values per pixel.
+const PIXEL_BYTES: usize = 92;
+
+#[derive(Clone, Debug)]
+pub struct Checkpointing {
+    pub path: PathBuf,
+    pub interval: Duration,
+}
+
+impl Checkpointing {
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        Self {
+            path: path.into(),
+            interval,
+        }
+    }
+}
+
+// Besides the film, a checkpoint records the settings that decide which
+// samples land where, so a resume with different ones can be refused.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub seed: u64,
+    pub sampler: SamplerKind,
+    pub filter: Filter,
+    // The pixels being rendered, the whole image unless cropped.
+    pub region: (Range<u32>, Range<u32>),
+    pub film: Film,
+}
+
+impl Checkpoint {
+    // Writes to a sibling file first and renames it into place, so a crash
+    // mid-write never destroys the previous checkpoint.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut out = Vec::with_capacity(24 + self.film.pixels.len() * PIXEL_BYTES);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&self.film.width.to_le_bytes());
+        out.extend_from_slice(&self.film.height.to_le_bytes());
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&sampler_tag(self.sampler).to_le_bytes());
+        let (tag, params) = filter_params(self.filter);
+        out.extend_from_slice(&tag.to_le_bytes());
+        for p in params {
+            out.extend_from_slice(&p.to_le_bytes());
+        }
+        let (xs, ys) = &self.region;
+        for n in [xs.start, xs.end, ys.start, ys.end] {
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+
+        for pixel in &self.film.pixels {
+            let estimate = &pixel.estimate;
+            let aov = &pixel.aov;
+            let floats = [
+                pixel.weighted_sum.to_array().as_slice(),
+                &[pixel.weight_sum, pixel.weighted_alpha],
+                &estimate.sum.to_array(),
+                &[estimate.luminance_sum, estimate.luminance_sq_sum],
+                &aov.normal_sum.to_array(),
+                &aov.albedo_sum.to_array(),
+                &aov.position_sum.to_array(),
+                &[aov.depth_sum],
+            ]
+            .concat();
+            for f in floats {
+                out.extend_from_slice(&f.to_le_bytes());
+            }
+            for n in [estimate.count, aov.hits, aov.object_id] {
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+        }
+
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        fs::write(&partial, out)?;
+        fs::rename(&partial, path)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        let mut reader = data.as_slice();
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut reader)? != VERSION {
+            return Err(invalid("not a trace-rs checkpoint"));
+        }
+
+        let width = read_u32(&mut reader)?;
+        let height = read_u32(&mut reader)?;
+        let mut seed = [0; 8];
+        reader.read_exact(&mut seed)?;
+        let sampler = sampler_from_tag(read_u32(&mut reader)?)?;
+        let tag = read_u32(&mut reader)?;
+        let mut params = [0.; 3];
+        for p in params.iter_mut() {
+            *p = f32::from_bits(read_u32(&mut reader)?);
+        }
+        let filter = filter_from_params(tag, params)?;
+        let mut region = [0; 4];
+        for n in region.iter_mut() {
+            *n = read_u32(&mut reader)?;
+        }
+
+        // The header's size is checked against the data before allocating.
+        let pixel_bytes = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(PIXEL_BYTES));
+        if pixel_bytes != Some(reader.len()) {
+            return Err(invalid("checkpoint size doesn't match its resolution"));
+        }
+
+        let mut film = Film::new(width, height);
+        for pixel in film.pixels.iter_mut() {
+            let mut f = [0.; 20];
+            for value in f.iter_mut() {
+                *value = f32::from_bits(read_u32(&mut reader)?);
+            }
+            *pixel = FilmPixel {
+                weighted_sum: Vec3::new(f[0], f[1], f[2]),
+                weight_sum: f[3],
+                weighted_alpha: f[4],
+                ..Default::default()
+            };
+            pixel.estimate.sum = Vec3::new(f[5], f[6], f[7]);
+            pixel.estimate.luminance_sum = f[8];
+            pixel.estimate.luminance_sq_sum = f[9];
+            pixel.aov.normal_sum = Vec3::new(f[10], f[11], f[12]);
+            pixel.aov.albedo_sum = Vec3::new(f[13], f[14], f[15]);
+            pixel.aov.position_sum = Vec3::new(f[16], f[17], f[18]);
+            pixel.aov.depth_sum = f[19];
+            pixel.estimate.count = read_u32(&mut reader)?;
+            pixel.aov.hits = read_u32(&mut reader)?;
+            pixel.aov.object_id = read_u32(&mut reader)?;
+        }
+
+        Ok(Self {
+            seed: u64::from_le_bytes(seed),
+            sampler,
+            filter,
+            region: (region[0]..region[1], region[2]..region[3]),
+            film,
+        })
+    }
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn sampler_tag(sampler: SamplerKind) -> u32 {
+    match sampler {
+        SamplerKind::Independent => 0,
+        SamplerKind::Stratified => 1,
+        SamplerKind::Halton => 2,
+        SamplerKind::Sobol => 3,
+    }
+}
+
+fn sampler_from_tag(tag: u32) -> io::Result<SamplerKind> {
+    Ok(match tag {
+        0 => SamplerKind::Independent,
+        1 => SamplerKind::Stratified,
+        2 => SamplerKind::Halton,
+        3 => SamplerKind::Sobol,
+        _ => return Err(invalid("unknown sampler in checkpoint")),
+    })
+}
+
+fn filter_params(filter: Filter) -> (u32, [f32; 3]) {
+    match filter {
+        Filter::Box { radius } => (0, [radius, 0., 0.]),
+        Filter::Tent { radius } => (1, [radius, 0., 0.]),
+        Filter::Gaussian { radius, sigma } => (2, [radius, sigma, 0.]),
+        Filter::MitchellNetravali { radius, b, c } => (3, [radius, b, c]),
+    }
+}
+
+fn filter_from_params(tag: u32, [radius, a, b]: [f32; 3]) -> io::Result<Filter> {
+    Ok(match tag {
+        0 => Filter::Box { radius },
+        1 => Filter::Tent { radius },
+        2 => Filter::Gaussian { radius, sigma: a },
+        3 => Filter::MitchellNetravali { radius, b: a, c: b },
+        _ => return Err(invalid("unknown filter in checkpoint")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_size_mismatches() {
+        let checkpoint = Checkpoint {
+            seed: 1,
+            sampler: SamplerKind::Sobol,
+            filter: Filter::default(),
+            region: (0..3, 0..2),
+            film: Film::new(3, 2),
+        };
+        let path = std::env::temp_dir().join("trace-rs-checkpoint-sizes.ckpt");
+        checkpoint.save(&path).unwrap();
+        let data = fs::read(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap().film.pixels.len(), 6);
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        fs::write(&path, trailing).unwrap();
+        let err = Checkpoint::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // A header claiming a huge image fails before allocating for it.
+        let mut huge = data[..data.len() - 6 * PIXEL_BYTES].to_vec();
+        huge[8..16].copy_from_slice(&[0, 0, 1, 0, 0, 0, 1, 0]);
+        fs::write(&path, huge).unwrap();
+        let err = Checkpoint::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,17 +1,108 @@
 use glam::Vec3;
 
 pub fn to_ppm(color: Vec3) -> String {
-    let c = color
-        .map(linear_to_gamma)
-        .clamp(Vec3::splat(0.000), Vec3::splat(0.999))
-        * 256.0;
+    let [r, g, b] = to_rgb8(color);
+    format!("{r} {g} {b}")
+}
+
+pub fn to_rgb8(color: Vec3) -> [u8; 3] {
+    let c = color.clamp(Vec3::splat(0.000), Vec3::splat(0.999)) * 256.0;
+
+    [c.x as u8, c.y as u8, c.z as u8]
+}
+
+pub fn to_rgba8(color: Vec3, alpha: f32) -> [u8; 4] {
+    let [r, g, b] = to_rgb8(color);
+    let a = alpha.clamp(0.000, 0.999) * 256.0;
+
+    [r, g, b, a as u8]
+}
+
+pub fn to_rgb16(color: Vec3) -> [u16; 3] {
+    let c = (color.clamp(Vec3::ZERO, Vec3::ONE) * 65535.0).round();
 
-    format!("{} {} {}", c.x as u8, c.y as u8, c.z as u8)
+    [c.x as u16, c.y as u16, c.z as u16]
 }
 
-pub fn linear_to_gamma(linear: f32) -> f32 {
-    if linear <= 0. {
-        return 0.;
+pub fn to_rgba16(color: Vec3, alpha: f32) -> [u16; 4] {
+    let [r, g, b] = to_rgb16(color);
+    let a = (alpha.clamp(0., 1.) * 65535.0).round();
+
+    [r, g, b, a as u16]
+}
+
+pub fn to_rgbe(color: Vec3) -> [u8; 4] {
+    let c = color.max(Vec3::ZERO);
+    let v = c.max_element();
+    if !v.is_finite() || v < 1e-32 {
+        return [0; 4];
+    }
+
+    let mut exponent = v.log2().floor() as i32 + 1;
+    if v * 2_f32.powi(-exponent) >= 1. {
+        exponent += 1;
+    }
+    let m = c * 2_f32.powi(8 - exponent);
+
+    [m.x as u8, m.y as u8, m.z as u8, (exponent + 128) as u8]
+}
+
+pub fn from_rgbe(rgbe: [u8; 4]) -> Vec3 {
+    let [r, g, b, e] = rgbe;
+    if e == 0 {
+        return Vec3::ZERO;
+    }
+    // Mantissas were truncated on the way in, so decode to the middle of
+    // each step.
+    let scale = 2_f32.powi(e as i32 - 136);
+    (Vec3::new(r as f32, g as f32, b as f32) + 0.5) * scale
+}
+
+pub fn linear_to_srgb(linear: f32) -> f32 {
+    if linear <= 0.0031308 {
+        return 12.92 * linear.max(0.);
+    }
+    1.055 * linear.powf(1. / 2.4) - 0.055
+}
+
+pub fn srgb_to_linear(encoded: f32) -> f32 {
+    if encoded <= 0.04045 {
+        return encoded.max(0.) / 12.92;
+    }
+    ((encoded + 0.055) / 1.055).powf(2.4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgbe_encoding() {
+        // The brightest channel's mantissa lands in 128..=255.
+        assert_eq!(to_rgbe(Vec3::new(1., 0.5, 0.25)), [128, 64, 32, 129]);
+        assert_eq!(to_rgbe(Vec3::new(0.75, 3., 0.)), [48, 192, 0, 130]);
+        assert_eq!(to_rgbe(Vec3::ZERO), [0; 4]);
+        assert_eq!(to_rgbe(Vec3::splat(-1.)), [0; 4]);
+    }
+
+    #[test]
+    fn rgbe_round_trip() {
+        for color in [
+            Vec3::new(0.5, 0.25, 0.125),
+            Vec3::new(1., 1., 1.),
+            Vec3::new(1000., 3., 0.001),
+            Vec3::new(1e-20, 2e-20, 0.),
+        ] {
+            let rgbe = to_rgbe(color);
+            let decoded = from_rgbe(rgbe);
+            // Each channel keeps 8 bits relative to the brightest one.
+            let step = color.max_element() / 128.;
+            assert!(
+                (decoded - color).abs().max_element() <= step,
+                "{color} came back as {decoded}"
+            );
+            assert_eq!(to_rgbe(decoded), rgbe);
+        }
+        assert_eq!(from_rgbe([0; 4]), Vec3::ZERO);
     }
-    linear.sqrt()
 }
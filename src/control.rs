@@ -0,0 +1,36 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+// Clones share the cancellation flag, so a clone kept by another thread can
+// stop a render that is running on the camera's copy. The flag stays set, and
+// every later render stops after its first sample per pixel, until `reset`
+// clears it.
+#[derive(Clone, Debug, Default)]
+pub struct RenderControl {
+    pub time_budget: Option<Duration>,
+    pub target_noise: Option<f32>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RenderControl {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
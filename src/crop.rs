@@ -0,0 +1,30 @@
+use std::ops::Range;
+
+// A pixel rectangle of the full image. Pixels inside it come out exactly as
+// they would in a full render, so crops of the same camera can be stitched.
+// The crop is clamped to the image; if nothing is left, `Camera::render`
+// panics and `Camera::resume` returns an error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Crop {
+    pub x: Range<u32>,
+    pub y: Range<u32>,
+    // Emit the full-size image with everything outside the crop left black
+    // rather than just the cropped pixels.
+    pub full_frame: bool,
+}
+
+impl Crop {
+    pub fn new(x: Range<u32>, y: Range<u32>) -> Self {
+        Self {
+            x,
+            y,
+            full_frame: false,
+        }
+    }
+
+    pub fn clamped(&self, width: u32, height: u32) -> (Range<u32>, Range<u32>) {
+        let x = self.x.start.min(width)..self.x.end.min(width);
+        let y = self.y.start.min(height)..self.y.end.min(height);
+        (x.start..x.end.max(x.start), y.start..y.end.max(y.start))
+    }
+}
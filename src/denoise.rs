@@ -0,0 +1,169 @@
+use crate::{framebuffer::Framebuffer, tone_map::luminance};
+use glam::Vec3;
+use rayon::prelude::*;
+
+const KERNEL: [f32; 5] = [1. / 16., 1. / 4., 3. / 8., 1. / 4., 1. / 16.];
+
+// Edge-avoiding à-trous wavelet filter (Dammertz et al. 2010). Each
+// iteration doubles the gap between taps, and neighbours are down-weighted
+// when their color, normal, albedo or depth differ from the center pixel.
+// Without AOVs on the framebuffer only the color edge stop applies.
+//
+// `color_sigma` is the color edge stop for a pixel with one sample; it
+// shrinks with the square root of each pixel's sample count, as the noise
+// does, so the same settings suit previews and near-final renders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Denoiser {
+    pub iterations: u32,
+    pub color_sigma: f32,
+    pub normal_sigma: f32,
+    pub albedo_sigma: f32,
+    pub depth_sigma: f32,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            iterations: 3,
+            color_sigma: 0.6,
+            normal_sigma: 0.1,
+            albedo_sigma: 0.1,
+            depth_sigma: 0.1,
+        }
+    }
+}
+
+struct Guide {
+    normal: Vec3,
+    albedo: Vec3,
+    depth: f32,
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn apply(&self, image: &Framebuffer) -> Framebuffer {
+        let width = image.width as usize;
+        let height = image.height as usize;
+        let guides = image.aovs.as_ref().map(|aovs| {
+            (0..image.pixels.len())
+                .map(|i| Guide {
+                    normal: aovs.normal[i],
+                    albedo: aovs.albedo[i],
+                    depth: aovs.depth[i],
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // Filtering the irradiance rather than the final color keeps texture
+        // detail that the albedo pass already knows about.
+        let demodulate = |i: usize| match &guides {
+            Some(guides) if guides[i].albedo != Vec3::ZERO => {
+                guides[i].albedo.max(Vec3::splat(1e-3))
+            }
+            _ => Vec3::ONE,
+        };
+        let color_sigmas = (0..image.pixels.len())
+            .map(|i| {
+                let count = image.sample_counts.get(i).copied().unwrap_or(0).max(1);
+                self.color_sigma / (count as f32).sqrt()
+            })
+            .collect::<Vec<_>>();
+        let mut current = image
+            .pixels
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| c / demodulate(i))
+            .collect::<Vec<_>>();
+
+        for iteration in 0..self.iterations {
+            let step = 1 << iteration;
+            let mut next = vec![Vec3::ZERO; current.len()];
+            next.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+                for (x, out) in row.iter_mut().enumerate() {
+                    *out = self.filter_pixel(
+                        &current,
+                        guides.as_deref(),
+                        (x, y),
+                        (width, height),
+                        step,
+                        &color_sigmas,
+                    );
+                }
+            });
+            current = next;
+        }
+
+        let pixels = current
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| c * demodulate(i))
+            .collect();
+        let mut denoised = Framebuffer::from_pixels(image.width, image.height, pixels);
+        denoised.sample_counts = image.sample_counts.clone();
+        denoised.alpha = image.alpha.clone();
+        denoised.aovs = image.aovs.clone();
+        denoised.metadata = image.metadata.clone();
+        denoised.metadata.push((
+            "Denoiser".to_string(),
+            format!("a-trous, {} iterations", self.iterations),
+        ));
+        denoised
+    }
+
+    fn filter_pixel(
+        &self,
+        colors: &[Vec3],
+        guides: Option<&[Guide]>,
+        (x, y): (usize, usize),
+        (width, height): (usize, usize),
+        step: usize,
+        color_sigmas: &[f32],
+    ) -> Vec3 {
+        let center = y * width + x;
+        let color_sigma = color_sigmas[center];
+        let center_color = colors[center];
+        let center_luminance = luminance(center_color);
+        let mut sum = Vec3::ZERO;
+        let mut weight_sum = 0.;
+
+        for (j, ky) in KERNEL.iter().enumerate() {
+            let qy = y as isize + (j as isize - 2) * step as isize;
+            if qy < 0 || qy >= height as isize {
+                continue;
+            }
+            for (i, kx) in KERNEL.iter().enumerate() {
+                let qx = x as isize + (i as isize - 2) * step as isize;
+                if qx < 0 || qx >= width as isize {
+                    continue;
+                }
+                let q = qy as usize * width + qx as usize;
+                let color = colors[q];
+
+                // Compare colors relative to their brightness so the same
+                // sigma works for dim interiors and bright highlights.
+                let color_delta =
+                    (color - center_color).length_squared() / (1. + center_luminance).powi(2);
+                let mut exponent = color_delta / (color_sigma * color_sigma);
+                if let Some(guides) = guides {
+                    let (p, q) = (&guides[center], &guides[q]);
+                    exponent += (p.normal - q.normal).length_squared()
+                        / (self.normal_sigma * self.normal_sigma);
+                    exponent += (p.albedo - q.albedo).length_squared()
+                        / (self.albedo_sigma * self.albedo_sigma);
+                    let depth_delta = (p.depth - q.depth) / (self.depth_sigma * p.depth.max(1e-3));
+                    exponent += depth_delta * depth_delta;
+                }
+
+                let weight = kx * ky * (-exponent).exp();
+                sum += color * weight;
+                weight_sum += weight;
+            }
+        }
+
+        // The center tap always contributes, so the weight sum is never zero.
+        sum / weight_sum
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{framebuffer::Framebuffer, input, sampler::Sampler, tone_map::luminance};
+use glam::{Quat, Vec2, Vec3};
+use std::{f32::consts::PI, io, path::Path, sync::Arc};
+
+// What rays that leave the scene see, and so what lights it.
+#[derive(Clone, Debug)]
+pub enum Environment {
+    Black,
+    Solid(Vec3),
+    // Blends from `bottom` straight down to `top` straight up.
+    Gradient {
+        up: Vec3,
+        bottom: Vec3,
+        top: Vec3,
+    },
+    // Blends from `horizon` to `zenith` above the horizon and to `ground`
+    // below it.
+    Sky {
+        up: Vec3,
+        ground: Vec3,
+        horizon: Vec3,
+        zenith: Vec3,
+    },
+    Map(Arc<EnvironmentMap>),
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::Gradient {
+            up: Vec3::Y,
+            bottom: Vec3::ONE,
+            top: Vec3::new(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+impl Environment {
+    pub fn radiance(&self, direction: Vec3) -> Vec3 {
+        match self {
+            Self::Black => Vec3::ZERO,
+            Self::Solid(color) => *color,
+            Self::Gradient { up, bottom, top } => {
+                let a = 0.5 * (direction.normalize().dot(up.normalize()) + 1.);
+                bottom.lerp(*top, a)
+            }
+            Self::Sky {
+                up,
+                ground,
+                horizon,
+                zenith,
+            } => {
+                let a = direction.normalize().dot(up.normalize());
+                if a >= 0. {
+                    horizon.lerp(*zenith, a)
+                } else {
+                    horizon.lerp(*ground, -a)
+                }
+            }
+            Self::Map(map) => map.radiance(direction),
+        }
+    }
+
+    // Picks a direction to send a shadow ray towards, drawing from the
+    // sampler only for environments that can be importance sampled.
+    pub fn sample(&self, sampler: &mut dyn Sampler) -> Option<EnvironmentSample> {
+        match self {
+            Self::Map(map) => map.sample(sampler.get_2d()),
+            _ => None,
+        }
+    }
+
+    // Solid angle density of `sample` returning `direction`.
+    pub fn pdf(&self, direction: Vec3) -> f32 {
+        match self {
+            Self::Map(map) => map.pdf(direction),
+            _ => 0.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnvironmentSample {
+    pub direction: Vec3,
+    pub radiance: Vec3,
+    pub pdf: f32,
+}
+
+// An equirectangular (latitude-longitude) image around the scene, with +Y at
+// the top row and -Z at the center column before `rotation`, in degrees
+// about +Y. Directions are importance sampled by luminance so small bright
+// sources such as a sun are found by shadow rays rather than by chance.
+#[derive(Clone, Debug)]
+pub struct EnvironmentMap {
+    pub rotation: f32,
+    pub intensity: f32,
+    image: Framebuffer,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Framebuffer) -> io::Result<Self> {
+        if image.width == 0 || image.height == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "environment map image is empty",
+            ));
+        }
+        let (width, height) = (image.width as usize, image.height as usize);
+        // Rows near the poles cover less of the sphere than the image suggests.
+        let weights = image
+            .pixels
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let theta = ((i / width) as f32 + 0.5) / height as f32 * PI;
+                luminance(c).max(0.) * theta.sin()
+            })
+            .collect::<Vec<_>>();
+        Ok(Self {
+            rotation: 0.,
+            intensity: 1.,
+            distribution: Distribution2D::new(&weights, width),
+            image,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(input::read_hdr(path)?)
+    }
+
+    pub fn radiance(&self, direction: Vec3) -> Vec3 {
+        let uv = self.to_uv(direction);
+        let width = self.image.width;
+        let height = self.image.height;
+        let x = ((uv.x * width as f32) as u32).min(width - 1);
+        let y = ((uv.y * height as f32) as u32).min(height - 1);
+        self.image.pixel(x, y) * self.intensity
+    }
+
+    pub fn sample(&self, u: Vec2) -> Option<EnvironmentSample> {
+        let (uv, uv_pdf) = self.distribution.sample(u);
+        let theta = uv.y * PI;
+        let phi = (uv.x - 0.5) * 2. * PI;
+        let sin_theta = theta.sin();
+        if uv_pdf == 0. || sin_theta == 0. {
+            return None;
+        }
+
+        let local = Vec3::new(sin_theta * phi.sin(), theta.cos(), -sin_theta * phi.cos());
+        let direction = self.orientation() * local;
+        Some(EnvironmentSample {
+            direction,
+            radiance: self.radiance(direction),
+            pdf: uv_pdf / (2. * PI * PI * sin_theta),
+        })
+    }
+
+    pub fn pdf(&self, direction: Vec3) -> f32 {
+        let uv = self.to_uv(direction);
+        let sin_theta = (uv.y * PI).sin();
+        if sin_theta == 0. {
+            return 0.;
+        }
+        self.distribution.pdf(uv) / (2. * PI * PI * sin_theta)
+    }
+
+    fn orientation(&self) -> Quat {
+        Quat::from_rotation_y(self.rotation.to_radians())
+    }
+
+    fn to_uv(&self, direction: Vec3) -> Vec2 {
+        let d = self.orientation().inverse() * direction.normalize();
+        Vec2::new(
+            0.5 + d.x.atan2(-d.z) / (2. * PI),
+            d.y.clamp(-1., 1.).acos() / PI,
+        )
+    }
+}
+
+// A piecewise-constant density over [0, 1).
+#[derive(Clone, Debug)]
+struct Distribution1D {
+    func: Vec<f32>,
+    cdf: Vec<f32>,
+    integral: f32,
+}
+
+impl Distribution1D {
+    fn new(func: Vec<f32>) -> Self {
+        let n = func.len();
+        let mut cdf = vec![0.; n + 1];
+        for i in 0..n {
+            cdf[i + 1] = cdf[i] + func[i] / n as f32;
+        }
+        let integral = cdf[n];
+        for (i, c) in cdf.iter_mut().enumerate().skip(1) {
+            // An all-black function falls back to uniform.
+            *c = if integral > 0. {
+                *c / integral
+            } else {
+                i as f32 / n as f32
+            };
+        }
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    // Returns the sampled point, its density and the bucket it fell in.
+    fn sample(&self, u: f32) -> (f32, f32, usize) {
+        let n = self.func.len();
+        let i = self
+            .cdf
+            .partition_point(|&c| c <= u)
+            .saturating_sub(1)
+            .min(n - 1);
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let du = if width > 0. {
+            (u - self.cdf[i]) / width
+        } else {
+            0.
+        };
+        ((i as f32 + du) / n as f32, self.density(i), i)
+    }
+
+    fn density(&self, i: usize) -> f32 {
+        if self.integral > 0. {
+            self.func[i] / self.integral
+        } else {
+            1.
+        }
+    }
+}
+
+// Samples a row from the marginal distribution, then a column within it.
+#[derive(Clone, Debug)]
+struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    fn new(func: &[f32], width: usize) -> Self {
+        let rows = func
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect::<Vec<_>>();
+        let marginal = Distribution1D::new(rows.iter().map(|row| row.integral).collect());
+        Self { rows, marginal }
+    }
+
+    fn sample(&self, u: Vec2) -> (Vec2, f32) {
+        let (v, row_pdf, row) = self.marginal.sample(u.y);
+        let (u, column_pdf, _) = self.rows[row].sample(u.x);
+        (Vec2::new(u, v), row_pdf * column_pdf)
+    }
+
+    fn pdf(&self, uv: Vec2) -> f32 {
+        let height = self.rows.len();
+        let row = ((uv.y * height as f32) as usize).min(height - 1);
+        let width = self.rows[row].func.len();
+        let column = ((uv.x * width as f32) as usize).min(width - 1);
+        self.marginal.density(row) * self.rows[row].density(column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_map_is_rejected() {
+        for (width, height) in [(0, 4), (4, 0)] {
+            let err = EnvironmentMap::new(Framebuffer::new(width, height)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    adaptive::PixelEstimate,
+    aov::{AovAccumulator, Aovs},
+    filter::Filter,
+    framebuffer::Framebuffer,
+};
+use glam::{Vec2, Vec3};
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FilmPixel {
+    pub weighted_sum: Vec3,
+    pub weight_sum: f32,
+    pub weighted_alpha: f32,
+    pub estimate: PixelEstimate,
+    pub aov: AovAccumulator,
+}
+
+impl FilmPixel {
+    pub fn merge(&mut self, other: &Self) {
+        self.weighted_sum += other.weighted_sum;
+        self.weight_sum += other.weight_sum;
+        self.weighted_alpha += other.weighted_alpha;
+        self.estimate.merge(&other.estimate);
+        self.aov.merge(&other.aov);
+    }
+
+    pub fn color(&self) -> Vec3 {
+        if self.weight_sum == 0. {
+            return Vec3::ZERO;
+        }
+        self.weighted_sum / self.weight_sum
+    }
+
+    pub fn alpha(&self) -> f32 {
+        if self.weight_sum == 0. {
+            return 0.;
+        }
+        self.weighted_alpha / self.weight_sum
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Film {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<FilmPixel>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![FilmPixel::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> &FilmPixel {
+        &self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn merge(&mut self, tile: &FilmTile) {
+        for (row, y) in tile.y.clone().enumerate() {
+            let tile_row = row * tile.width() as usize;
+            let film_row = (y * self.width + tile.x.start) as usize;
+            for i in 0..tile.width() as usize {
+                self.pixels[film_row + i].merge(&tile.pixels[tile_row + i]);
+            }
+        }
+    }
+
+    // The fewest samples taken by any pixel in the region.
+    pub fn min_samples(&self, x: Range<u32>, y: Range<u32>) -> u32 {
+        y.flat_map(|row| {
+            x.clone()
+                .map(move |col| self.pixel(col, row).estimate.count)
+        })
+        .min()
+        .unwrap_or(0)
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.pixels.iter().map(|p| p.estimate.count as u64).sum()
+    }
+
+    // Pixels that never got a sample, such as those outside a crop, are left
+    // out.
+    pub fn mean_relative_error(&self) -> f32 {
+        let sampled = self.pixels.iter().filter(|p| p.estimate.count > 0);
+        let (total, count) = sampled.fold((0., 0), |(total, count), p| {
+            (total + p.estimate.relative_error(), count + 1)
+        });
+        total / count.max(1) as f32
+    }
+
+    pub fn crop(&self, x: Range<u32>, y: Range<u32>) -> Self {
+        let mut pixels = Vec::with_capacity(x.len() * y.len());
+        for row in y.clone() {
+            let start = (row * self.width) as usize;
+            pixels
+                .extend_from_slice(&self.pixels[start + x.start as usize..start + x.end as usize]);
+        }
+        Self {
+            width: x.len() as u32,
+            height: y.len() as u32,
+            pixels,
+        }
+    }
+
+    // Clears every pixel outside the rectangle, keeping the film's size.
+    pub fn mask(&self, x: Range<u32>, y: Range<u32>) -> Self {
+        let mut film = Self::new(self.width, self.height);
+        for row in y {
+            let start = (row * self.width) as usize;
+            let range = start + x.start as usize..start + x.end as usize;
+            film.pixels[range.clone()].copy_from_slice(&self.pixels[range]);
+        }
+        film
+    }
+
+    pub fn aovs(&self) -> Aovs {
+        let accumulators = self
+            .pixels
+            .iter()
+            .map(|p| (p.aov, p.estimate.count))
+            .collect::<Vec<_>>();
+        Aovs::resolve(&accumulators)
+    }
+
+    pub fn alpha(&self) -> Vec<f32> {
+        self.pixels.iter().map(FilmPixel::alpha).collect()
+    }
+
+    pub fn to_framebuffer(&self) -> Framebuffer {
+        let pixels = self.pixels.iter().map(FilmPixel::color).collect();
+        let mut image = Framebuffer::from_pixels(self.width, self.height, pixels);
+        image.sample_counts = self.pixels.iter().map(|p| p.estimate.count).collect();
+        image
+    }
+}
+
+// A rectangle of film that one worker splats into without synchronisation.
+// Its bounds include the filter's reach past the pixels it owns, so merging
+// tiles back into the film must happen in a fixed order to stay deterministic.
+#[derive(Clone, Debug)]
+pub struct FilmTile {
+    pub x: Range<u32>,
+    pub y: Range<u32>,
+    pub pixels: Vec<FilmPixel>,
+    filter: Filter,
+}
+
+impl FilmTile {
+    pub fn new(x: Range<u32>, y: Range<u32>, filter: Filter) -> Self {
+        let len = x.len() * y.len();
+        Self {
+            x,
+            y,
+            pixels: vec![FilmPixel::default(); len],
+            filter,
+        }
+    }
+
+    // The tile needed to own `x` by `y` on a `width` by `height` film,
+    // grown by the filter radius and clipped to the film.
+    pub fn covering(x: Range<u32>, y: Range<u32>, width: u32, height: u32, filter: Filter) -> Self {
+        let reach = filter.reach();
+        Self::new(
+            x.start.saturating_sub(reach)..(x.end + reach).min(width),
+            y.start.saturating_sub(reach)..(y.end + reach).min(height),
+            filter,
+        )
+    }
+
+    pub fn width(&self) -> u32 {
+        self.x.end - self.x.start
+    }
+
+    // `alpha` is the sample's coverage, filtered the same way as its color.
+    pub fn add_sample(&mut self, position: Vec2, color: Vec3, alpha: f32) {
+        let radius = self.filter.radius();
+        let x0 = ((position.x - 0.5 - radius).ceil() as i64).max(self.x.start as i64);
+        let x1 = ((position.x - 0.5 + radius).floor() as i64).min(self.x.end as i64 - 1);
+        let y0 = ((position.y - 0.5 - radius).ceil() as i64).max(self.y.start as i64);
+        let y1 = ((position.y - 0.5 + radius).floor() as i64).min(self.y.end as i64 - 1);
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let weight = self
+                    .filter
+                    .evaluate(position.x - (x as f32 + 0.5), position.y - (y as f32 + 0.5));
+                if weight == 0. {
+                    continue;
+                }
+                let pixel = self.pixel_mut(x as u32, y as u32);
+                pixel.weighted_sum += weight * color;
+                pixel.weight_sum += weight;
+                pixel.weighted_alpha += weight * alpha;
+            }
+        }
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.pixels.iter().map(|p| p.estimate.count as u64).sum()
+    }
+
+    pub fn set_samples(&mut self, x: u32, y: u32, estimate: PixelEstimate, aov: AovAccumulator) {
+        let pixel = self.pixel_mut(x, y);
+        pixel.estimate = estimate;
+        pixel.aov = aov;
+    }
+
+    fn pixel_mut(&mut self, x: u32, y: u32) -> &mut FilmPixel {
+        let index = ((y - self.y.start) * self.width() + (x - self.x.start)) as usize;
+        &mut self.pixels[index]
+    }
+}
@@ -0,0 +1,82 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    Box { radius: f32 },
+    Tent { radius: f32 },
+    Gaussian { radius: f32, sigma: f32 },
+    MitchellNetravali { radius: f32, b: f32, c: f32 },
+}
+
+impl Filter {
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Self::Box { radius }
+            | Self::Tent { radius }
+            | Self::Gaussian { radius, .. }
+            | Self::MitchellNetravali { radius, .. } => radius,
+        }
+    }
+
+    // How many pixels past its own a sample can land on.
+    pub fn reach(&self) -> u32 {
+        (self.radius() - 0.5).ceil().max(0.) as u32
+    }
+
+    pub fn evaluate(&self, dx: f32, dy: f32) -> f32 {
+        match *self {
+            // Half open, so a sample on a pixel edge counts in one pixel only.
+            Self::Box { radius } => {
+                if (-radius..radius).contains(&dx) && (-radius..radius).contains(&dy) {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            Self::Tent { radius } => (radius - dx.abs()).max(0.) * (radius - dy.abs()).max(0.),
+            Self::Gaussian { radius, sigma } => {
+                let gaussian = |d: f32| {
+                    let edge = (-radius * radius / (2. * sigma * sigma)).exp();
+                    ((-d * d / (2. * sigma * sigma)).exp() - edge).max(0.)
+                };
+                gaussian(dx) * gaussian(dy)
+            }
+            Self::MitchellNetravali { radius, b, c } => {
+                mitchell_1d(2. * dx / radius, b, c) * mitchell_1d(2. * dy / radius, b, c)
+            }
+        }
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::Box { radius: 0.5 }
+    }
+}
+
+fn mitchell_1d(x: f32, b: f32, c: f32) -> f32 {
+    let x = x.abs();
+    let value = if x <= 1. {
+        (12. - 9. * b - 6. * c) * x.powi(3) + (-18. + 12. * b + 6. * c) * x.powi(2) + (6. - 2. * b)
+    } else if x <= 2. {
+        (-b - 6. * c) * x.powi(3)
+            + (6. * b + 30. * c) * x.powi(2)
+            + (-12. * b - 48. * c) * x
+            + (8. * b + 24. * c)
+    } else {
+        0.
+    };
+    value / 6.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_edges_count_once() {
+        let filter = Filter::default();
+        // A sample on the corner of four pixels, seen from each of them.
+        let weights = [(-0.5, -0.5), (0.5, -0.5), (-0.5, 0.5), (0.5, 0.5)]
+            .map(|(dx, dy)| filter.evaluate(dx, dy));
+        assert_eq!(weights, [1., 0., 0., 0.]);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{
+    aov::{Aov, Aovs},
+    output::ImageWriter,
+};
+use glam::Vec3;
+use std::{io, path::Path};
+
+#[derive(Clone, Debug, Default)]
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Vec3>,
+    pub sample_counts: Vec<u32>,
+    // Coverage per pixel; colors are premultiplied by it.
+    pub alpha: Option<Vec<f32>>,
+    pub aovs: Option<Aovs>,
+    pub metadata: Vec<(String, String)>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::from_pixels(
+            width,
+            height,
+            vec![Vec3::ZERO; width as usize * height as usize],
+        )
+    }
+
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Vec3>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize,
+            "pixel count does not match {width}x{height}"
+        );
+        Self {
+            width,
+            height,
+            sample_counts: vec![0; pixels.len()],
+            pixels,
+            alpha: None,
+            aovs: None,
+            metadata: vec![],
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Vec3 {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Vec3) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    pub fn aov(&self, aov: Aov) -> Option<Self> {
+        let aovs = self.aovs.as_ref()?;
+        let mut image = Self::from_pixels(self.width, self.height, aovs.colors(aov));
+        image.metadata = self.metadata.clone();
+        Some(image)
+    }
+
+    pub fn sample_count_image(&self) -> Self {
+        let max = self.sample_counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let pixels = self
+            .sample_counts
+            .iter()
+            .map(|&count| Vec3::splat(count as f32 / max))
+            .collect();
+        Self::from_pixels(self.width, self.height, pixels)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        ImageWriter::new().write(self, path)
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
@@ -1,22 +1,31 @@
 use crate::{aabb::Aabb, material::Material, ray::Ray};
-use glam::Vec3;
-use std::ops::Range;
+use glam::{Vec2, Vec3};
+use std::{ops::Range, sync::Arc};
 
 #[derive(Clone, Debug)]
-pub struct HitRecord {
+pub struct HitRecord<'a> {
     pub p: Vec3,
     pub normal: Vec3,
     pub t: f32,
+    pub u: f32,
+    pub v: f32,
+    // How the position changes with u and v, used to size texture lookups.
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
+    // Width of the ray's pixel footprint where it hit, in world units.
+    pub cone_width: f32,
     pub front_face: bool,
-    pub material: Material,
+    pub material: &'a Material,
+    pub object_id: u32,
 }
 
-impl HitRecord {
+impl<'a> HitRecord<'a> {
     pub fn with_front_face(
         p: Vec3,
         t: f32,
         outward_normal: Vec3,
-        material: Material,
+        (u, v): (f32, f32),
+        material: &'a Material,
         ray: &Ray,
     ) -> Self {
         let front_face = ray.direction.dot(outward_normal) < 0.;
@@ -30,14 +39,55 @@ impl HitRecord {
             p,
             normal,
             t,
+            u,
+            v,
+            dpdu: Vec3::ZERO,
+            dpdv: Vec3::ZERO,
+            cone_width: 0.,
             front_face,
             material,
+            object_id: 0,
         }
     }
+
+    // The UV extent the ray's footprint covers. Surfaces that don't report
+    // derivatives get point lookups.
+    pub fn uv_footprint(&self) -> Vec2 {
+        let extent = |dp: Vec3| {
+            let length = dp.length();
+            if length > 0. {
+                self.cone_width / length
+            } else {
+                0.
+            }
+        };
+        Vec2::new(extent(self.dpdu), extent(self.dpdv))
+    }
 }
 
 pub trait Hittable {
-    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord>;
+    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord<'_>>;
 
     fn bounding_box(&self) -> &Aabb;
+
+    fn set_object_id(&mut self, _id: u32) {}
+}
+
+// Lets one list hold different kinds of primitive, as
+// `HittableList<Arc<dyn Hittable + Send + Sync>>`. IDs can only be set
+// while the object isn't shared.
+impl<T: Hittable + ?Sized> Hittable for Arc<T> {
+    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord<'_>> {
+        (**self).hit(ray, interval)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        (**self).bounding_box()
+    }
+
+    fn set_object_id(&mut self, id: u32) {
+        if let Some(object) = Arc::get_mut(self) {
+            object.set_object_id(id);
+        }
+    }
 }
@@ -25,9 +25,23 @@ where
         }
     }
 
-    pub fn add(&mut self, object: T) {
-        self.objects.push(object.clone());
-        self.bbox = Aabb::from_boxes(self.bbox.clone(), object.bounding_box());
+    // Objects are numbered from 1 in the order they are added, leaving 0
+    // for the background in object ID passes.
+    pub fn add(&mut self, mut object: T) {
+        object.set_object_id(self.objects.len() as u32 + 1);
+        self.push(object);
+    }
+
+    // Adds without renumbering, for regrouping objects that already have IDs.
+    pub(crate) fn push(&mut self, object: T) {
+        // The empty list's box is a point at the origin, which shouldn't
+        // stretch the box of objects far from it.
+        self.bbox = if self.objects.is_empty() {
+            object.bounding_box().clone()
+        } else {
+            Aabb::from_boxes(self.bbox.clone(), object.bounding_box())
+        };
+        self.objects.push(object);
     }
 }
 
@@ -35,7 +49,7 @@ impl<T> Hittable for HittableList<T>
 where
     T: Hittable + 'static,
 {
-    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord<'_>> {
         self.objects
             .iter()
             .fold((None, interval.end), |(acc, max), object| {
@@ -51,6 +65,13 @@ where
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    // A list inside another list counts as one object.
+    fn set_object_id(&mut self, id: u32) {
+        for object in &mut self.objects {
+            object.set_object_id(id);
+        }
+    }
 }
 
 impl<T> Default for HittableList<T>
@@ -0,0 +1,247 @@
+use crate::{color, framebuffer::Framebuffer};
+use glam::Vec3;
+use std::{
+    fs::{self, File},
+    io::{self, BufReader},
+    path::Path,
+};
+
+// Reads an image by extension. PNG and JPEG values are returned as stored,
+// without undoing their sRGB encoding; HDR values are linear.
+pub fn read_image(path: impl AsRef<Path>) -> io::Result<Framebuffer> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => read_png(path),
+        "jpg" | "jpeg" => read_jpeg(path),
+        "hdr" => read_hdr(path),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot infer image format from {}", path.display()),
+        )),
+    }
+}
+
+// Alpha is dropped and palettes and low bit depths are expanded.
+pub fn read_png(path: impl AsRef<Path>) -> io::Result<Framebuffer> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info()?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data)?;
+    let data = &data[..info.buffer_size()];
+
+    let (color_type, depth) = reader.output_color_type();
+    let values = match depth {
+        png::BitDepth::Sixteen => data
+            .chunks_exact(2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as f32 / 65535.)
+            .collect::<Vec<_>>(),
+        _ => data.iter().map(|&b| b as f32 / 255.).collect(),
+    };
+    let pixels = to_pixels(&values, color_type.samples());
+    Ok(Framebuffer::from_pixels(info.width, info.height, pixels))
+}
+
+pub fn read_jpeg(path: impl AsRef<Path>) -> io::Result<Framebuffer> {
+    let mut decoder = jpeg_decoder::Decoder::new(BufReader::new(File::open(path)?));
+    let data = decoder.decode().map_err(|err| invalid(&err.to_string()))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| invalid("missing JPEG header"))?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => to_pixels(
+            &data.iter().map(|&b| b as f32 / 255.).collect::<Vec<_>>(),
+            1,
+        ),
+        jpeg_decoder::PixelFormat::L16 => to_pixels(
+            &data
+                .chunks_exact(2)
+                .map(|b| u16::from_ne_bytes([b[0], b[1]]) as f32 / 65535.)
+                .collect::<Vec<_>>(),
+            1,
+        ),
+        jpeg_decoder::PixelFormat::RGB24 => to_pixels(
+            &data.iter().map(|&b| b as f32 / 255.).collect::<Vec<_>>(),
+            3,
+        ),
+        jpeg_decoder::PixelFormat::CMYK32 => return Err(invalid("CMYK JPEGs are not supported")),
+    };
+    Ok(Framebuffer::from_pixels(
+        info.width as u32,
+        info.height as u32,
+        pixels,
+    ))
+}
+
+// Gray and gray-alpha become gray RGB; any fourth channel is alpha.
+fn to_pixels(values: &[f32], channels: usize) -> Vec<Vec3> {
+    values
+        .chunks_exact(channels)
+        .map(|c| match channels {
+            1 | 2 => Vec3::splat(c[0]),
+            _ => Vec3::new(c[0], c[1], c[2]),
+        })
+        .collect()
+}
+
+// Reads a Radiance RGBE image, either flat or with the run-length encoded
+// scanlines most tools write.
+pub fn read_hdr(path: impl AsRef<Path>) -> io::Result<Framebuffer> {
+    let data = fs::read(path)?;
+    let mut pos = 0;
+    let mut next_line = || {
+        let end = data[pos..].iter().position(|&b| b == b'\n')?;
+        let line = String::from_utf8_lossy(&data[pos..pos + end]).into_owned();
+        pos += end + 1;
+        Some(line)
+    };
+
+    let magic = next_line().unwrap_or_default();
+    if !magic.starts_with("#?") {
+        return Err(invalid("not a Radiance HDR file"));
+    }
+    loop {
+        match next_line() {
+            Some(line) if line.is_empty() => break,
+            Some(line) if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" => {
+                return Err(invalid(&format!("unsupported HDR {line}")));
+            }
+            Some(_) => {}
+            None => return Err(invalid("truncated HDR header")),
+        }
+    }
+
+    let resolution = next_line().ok_or_else(|| invalid("missing HDR resolution"))?;
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => (height.parse::<u32>(), width.parse::<u32>()),
+        _ => {
+            return Err(invalid(&format!(
+                "unsupported HDR orientation {resolution}"
+            )))
+        }
+    };
+    let (Ok(height), Ok(width)) = (height, width) else {
+        return Err(invalid(&format!("bad HDR resolution {resolution}")));
+    };
+
+    // Run-length encoding fits at most 127 values in two bytes, so a file
+    // can't hold more than 16 pixels per byte. Anything larger is checked
+    // before allocating for it.
+    let mut rest = &data[pos..];
+    if width as u64 * height as u64 > 16 * rest.len() as u64 {
+        return Err(invalid(&format!(
+            "HDR resolution {resolution} exceeds its data"
+        )));
+    }
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for _ in 0..height {
+        let scanline = read_scanline(&mut rest, width as usize)?;
+        pixels.extend(scanline.into_iter().map(color::from_rgbe));
+    }
+    Ok(Framebuffer::from_pixels(width, height, pixels))
+}
+
+fn read_scanline(data: &mut &[u8], width: usize) -> io::Result<Vec<[u8; 4]>> {
+    let mut scanline = vec![[0; 4]; width];
+    let encoded = (8..0x8000).contains(&width)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && ((data[2] as usize) << 8 | data[3] as usize) == width;
+
+    if !encoded {
+        let bytes = take(data, width * 4)?;
+        for (pixel, rgbe) in scanline.iter_mut().zip(bytes.chunks_exact(4)) {
+            pixel.copy_from_slice(rgbe);
+        }
+        return Ok(scanline);
+    }
+
+    // Each channel is stored separately as a mix of runs and literal spans.
+    *data = &data[4..];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = take(data, 1)?[0] as usize;
+            let (run, count) = if count > 128 {
+                (true, count - 128)
+            } else {
+                (false, count)
+            };
+            if count == 0 || x + count > width {
+                return Err(invalid("corrupt HDR scanline"));
+            }
+            if run {
+                let value = take(data, 1)?[0];
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = value;
+                }
+            } else {
+                for (pixel, &value) in scanline[x..x + count].iter_mut().zip(take(data, count)?) {
+                    pixel[channel] = value;
+                }
+            }
+            x += count;
+        }
+    }
+    Ok(scanline)
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if data.len() < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated HDR pixel data",
+        ));
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Ok(head)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::ImageWriter;
+    use glam::Vec3;
+
+    #[test]
+    fn hdr_round_trip() {
+        let pixels = (0..12)
+            .map(|i| Vec3::new(i as f32 * 0.37, 1. / (i + 1) as f32, 8. - i as f32))
+            .collect::<Vec<_>>();
+        let image = Framebuffer::from_pixels(4, 3, pixels);
+        let path = std::env::temp_dir().join("trace-rs-hdr-round-trip.hdr");
+        ImageWriter::new().write(&image, &path).unwrap();
+        let read = read_hdr(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!((read.width, read.height), (4, 3));
+        for (read, written) in read.pixels.iter().zip(&image.pixels) {
+            assert_eq!(*read, color::from_rgbe(color::to_rgbe(*written)));
+        }
+    }
+
+    #[test]
+    fn hdr_resolution_beyond_data() {
+        let path = std::env::temp_dir().join("trace-rs-hdr-too-large.hdr");
+        fs::write(
+            &path,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 65536 +X 65536\n\0\0\0\0",
+        )
+        .unwrap();
+        let err = read_hdr(&path).unwrap_err();
+        let _ = fs::remove_file(&path);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,10 +1,32 @@
 pub mod aabb;
+pub mod adaptive;
+pub mod aov;
 pub mod bvh_node;
 pub mod camera;
+pub mod checkpoint;
 pub mod color;
+pub mod control;
+pub mod crop;
+pub mod denoise;
+pub mod environment;
+pub mod film;
+pub mod filter;
+pub mod framebuffer;
 pub mod hittable;
 pub mod hittable_list;
+pub mod input;
 pub mod material;
+pub mod mesh;
+pub mod noise;
+pub mod output;
+pub mod progress;
+pub mod progressive;
+pub mod quad;
+pub mod random;
 pub mod ray;
+pub mod sampler;
 pub mod sphere;
+pub mod stats;
+pub mod texture;
+pub mod tone_map;
 pub mod vector;
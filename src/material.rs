@@ -1,6 +1,8 @@
-use crate::{hittable::HitRecord, ray::Ray, vector::random_unit_vector};
+use crate::{
+    hittable::HitRecord, ray::Ray, sampler::Sampler, texture::Texture, vector::sample_unit_vector,
+};
 use glam::Vec3;
-use rand::Rng;
+use std::f32::consts::PI;
 
 pub struct ScatterRecord {
     pub attenuation: Vec3,
@@ -9,16 +11,62 @@ pub struct ScatterRecord {
 
 #[derive(Clone, Debug)]
 pub enum Material {
-    Lambertian(Vec3),
-    Metal(Vec3, f32),
+    Lambertian(Texture),
+    // Albedo and fuzz, which reads the texture's first channel.
+    Metal(Texture, Texture),
     Dieletric(f32),
+    // Emits `color * intensity` and scatters nothing. One-sided lights only
+    // emit from the side their outward normal faces.
+    DiffuseLight {
+        color: Vec3,
+        intensity: f32,
+        one_sided: bool,
+    },
 }
 
 impl Material {
-    pub fn scatter(&self, ray: &Ray, hit_rec: &HitRecord) -> Option<ScatterRecord> {
+    pub fn albedo(&self, hit_rec: &HitRecord) -> Vec3 {
+        match self {
+            Self::Lambertian(albedo) | Self::Metal(albedo, _) => albedo.evaluate(hit_rec),
+            Self::Dieletric(_) => Vec3::ONE,
+            Self::DiffuseLight { .. } => Vec3::ZERO,
+        }
+    }
+
+    pub fn emitted(&self, hit_rec: &HitRecord) -> Vec3 {
+        match self {
+            Self::DiffuseLight {
+                color,
+                intensity,
+                one_sided,
+            } if hit_rec.front_face || !one_sided => *color * *intensity,
+            _ => Vec3::ZERO,
+        }
+    }
+
+    // Diffuse materials scatter over the whole hemisphere, so it pays to
+    // sample lights directly from them.
+    pub fn is_diffuse(&self) -> bool {
+        matches!(self, Self::Lambertian(_))
+    }
+
+    // Solid angle density of `scatter` choosing `direction`.
+    pub fn scattering_pdf(&self, hit_rec: &HitRecord, direction: Vec3) -> f32 {
+        match self {
+            Self::Lambertian(_) => (hit_rec.normal.dot(direction.normalize()) / PI).max(0.),
+            Self::Metal(..) | Self::Dieletric(_) | Self::DiffuseLight { .. } => 0.,
+        }
+    }
+
+    pub fn scatter(
+        &self,
+        ray: &Ray,
+        hit_rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterRecord> {
         match self {
             Self::Lambertian(albedo) => {
-                let mut scatter_direction = hit_rec.normal + random_unit_vector();
+                let mut scatter_direction = hit_rec.normal + sample_unit_vector(sampler.get_2d());
 
                 if scatter_direction.abs().cmplt(Vec3::splat(1e-8)).all() {
                     scatter_direction = hit_rec.normal
@@ -29,13 +77,14 @@ impl Material {
                     time: ray.time,
                 };
                 Some(ScatterRecord {
-                    attenuation: *albedo,
+                    attenuation: albedo.evaluate(hit_rec),
                     scattered,
                 })
             }
             Self::Metal(albedo, fuzz) => {
+                let fuzz = fuzz.evaluate(hit_rec).x;
                 let mut reflected = ray.direction.reflect(hit_rec.normal);
-                reflected = reflected.normalize() + (fuzz * random_unit_vector());
+                reflected = reflected.normalize() + (fuzz * sample_unit_vector(sampler.get_2d()));
                 let scattered = Ray {
                     origin: hit_rec.p,
                     direction: reflected,
@@ -43,7 +92,7 @@ impl Material {
                 };
                 if scattered.direction.dot(hit_rec.normal) > 0. {
                     Some(ScatterRecord {
-                        attenuation: *albedo,
+                        attenuation: albedo.evaluate(hit_rec),
                         scattered,
                     })
                 } else {
@@ -61,9 +110,8 @@ impl Material {
                 let cos_theta = -unit_direction.dot(hit_rec.normal).min(1.);
                 let sin_theta = (1. - cos_theta * cos_theta).sqrt();
 
-                let mut rng = rand::thread_rng();
                 let direction =
-                    if ri * sin_theta > 1. || reflectance(cos_theta, ri) > rng.gen::<f32>() {
+                    if ri * sin_theta > 1. || reflectance(cos_theta, ri) > sampler.get_1d() {
                         unit_direction.reflect(hit_rec.normal)
                     } else {
                         unit_direction.refract(hit_rec.normal, ri)
@@ -80,6 +128,7 @@ impl Material {
                     scattered,
                 })
             }
+            Self::DiffuseLight { .. } => None,
         }
     }
 }
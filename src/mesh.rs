@@ -0,0 +1,272 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    hittable_list::HittableList,
+    material::Material,
+    ray::Ray,
+    stats,
+};
+use glam::{Vec2, Vec3};
+use std::{ops::Range, sync::Arc};
+
+// Triangles indexing into shared vertex data. `normals` and `uvs` are either
+// empty or hold one entry per position; without normals the mesh is flat
+// shaded, and without UVs each triangle spans (0, 0), (1, 0) and (0, 1).
+#[derive(Clone, Debug)]
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<Vec2>,
+    pub indices: Vec<[u32; 3]>,
+    pub material: Material,
+}
+
+impl Mesh {
+    pub fn new(positions: Vec<Vec3>, indices: Vec<[u32; 3]>, material: Material) -> Self {
+        Self {
+            positions,
+            normals: vec![],
+            uvs: vec![],
+            indices,
+            material,
+        }
+    }
+
+    // One handle per triangle, all sharing this mesh and counting as a single
+    // object.
+    pub fn triangles(self) -> HittableList<Triangle> {
+        assert!(
+            self.normals.is_empty() || self.normals.len() == self.positions.len(),
+            "mesh needs no normals or one per position"
+        );
+        assert!(
+            self.uvs.is_empty() || self.uvs.len() == self.positions.len(),
+            "mesh needs no UVs or one per position"
+        );
+
+        let mesh = Arc::new(self);
+        let mut triangles = HittableList::new();
+        for index in 0..mesh.indices.len() as u32 {
+            let [p0, p1, p2] = mesh.vertices(index);
+            triangles.push(Triangle {
+                mesh: mesh.clone(),
+                index,
+                object_id: 1,
+                bbox: Aabb::from_points(p0.min(p1).min(p2), p0.max(p1).max(p2)),
+            });
+        }
+        triangles
+    }
+
+    fn vertices(&self, index: u32) -> [Vec3; 3] {
+        self.indices[index as usize].map(|i| self.positions[i as usize])
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Triangle {
+    mesh: Arc<Mesh>,
+    index: u32,
+    object_id: u32,
+    bbox: Aabb,
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord<'_>> {
+        stats::count(|s| s.triangle_tests += 1);
+        let mesh = &*self.mesh;
+        let vertices = mesh.vertices(self.index);
+        let (b, t) = intersect(ray, vertices)?;
+        if !interval.contains(&t) {
+            return None;
+        }
+
+        let [p0, p1, p2] = vertices;
+        let p = b.x * p0 + b.y * p1 + b.z * p2;
+        let [i0, i1, i2] = mesh.indices[self.index as usize].map(|i| i as usize);
+
+        let uvs = if mesh.uvs.is_empty() {
+            [Vec2::ZERO, Vec2::X, Vec2::Y]
+        } else {
+            [mesh.uvs[i0], mesh.uvs[i1], mesh.uvs[i2]]
+        };
+        let uv = b.x * uvs[0] + b.y * uvs[1] + b.z * uvs[2];
+        let (dpdu, dpdv) = derivatives(vertices, uvs);
+
+        // The winding decides which side is outside unless vertex normals say
+        // otherwise. Shading normals are kept on the same side as the face.
+        let mut outward = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+        let shading = if mesh.normals.is_empty() {
+            None
+        } else {
+            let n = b.x * mesh.normals[i0] + b.y * mesh.normals[i1] + b.z * mesh.normals[i2];
+            n.try_normalize()
+        };
+        if shading.is_some_and(|n| n.dot(outward) < 0.) {
+            outward = -outward;
+        }
+
+        let hit_rec = HitRecord::with_front_face(p, t, outward, (uv.x, uv.y), &mesh.material, ray);
+        let normal = match shading {
+            Some(n) if hit_rec.front_face => n,
+            Some(n) => -n,
+            None => hit_rec.normal,
+        };
+        Some(HitRecord {
+            normal,
+            dpdu,
+            dpdv,
+            object_id: self.object_id,
+            ..hit_rec
+        })
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn set_object_id(&mut self, id: u32) {
+        self.object_id = id;
+    }
+}
+
+// Watertight ray-triangle intersection (Woop, Benthin and Wald 2013): the
+// vertices are moved into a space where the ray runs down +z from the origin,
+// so rays through a shared edge or vertex always hit one of its triangles.
+// Returns the barycentric weights of the three vertices and the ray's t.
+fn intersect(ray: &Ray, vertices: [Vec3; 3]) -> Option<(Vec3, f32)> {
+    let d = ray.direction;
+    let a = d.abs();
+    let kz = if a.x > a.y && a.x > a.z {
+        0
+    } else if a.y > a.z {
+        1
+    } else {
+        2
+    };
+    let kx = (kz + 1) % 3;
+    let ky = (kx + 1) % 3;
+    let permute = |v: Vec3| Vec3::new(v[kx], v[ky], v[kz]);
+    let d = permute(d);
+    if d.z == 0. {
+        return None;
+    }
+    let shear = Vec3::new(-d.x / d.z, -d.y / d.z, 1. / d.z);
+
+    let [p0, p1, p2] = vertices.map(|v| {
+        let p = permute(v - ray.origin);
+        Vec3::new(p.x + shear.x * p.z, p.y + shear.y * p.z, p.z)
+    });
+
+    let e = Vec3::new(edge(p1, p2), edge(p2, p0), edge(p0, p1));
+    if e.min_element() < 0. && e.max_element() > 0. {
+        return None;
+    }
+    let det = e.x + e.y + e.z;
+    if det == 0. {
+        return None;
+    }
+
+    let t_scaled = shear.z * (e.x * p0.z + e.y * p1.z + e.z * p2.z);
+    let t = t_scaled / det;
+    if !t.is_finite() {
+        return None;
+    }
+    Some((e / det, t))
+}
+
+// Twice the signed area of the triangle the ray makes with edge `a` to `b`
+// after the shear. An exact zero may be rounding, so it is redone in double
+// precision; both triangles sharing the edge then still agree on its sign.
+fn edge(a: Vec3, b: Vec3) -> f32 {
+    let e = a.x * b.y - a.y * b.x;
+    if e != 0. {
+        return e;
+    }
+    (a.x as f64 * b.y as f64 - a.y as f64 * b.x as f64) as f32
+}
+
+// Solves for how the position changes with u and v. Degenerate UVs fall back
+// to the edges from the first vertex.
+fn derivatives([p0, p1, p2]: [Vec3; 3], [uv0, uv1, uv2]: [Vec2; 3]) -> (Vec3, Vec3) {
+    let (duv02, duv12) = (uv0 - uv2, uv1 - uv2);
+    let (dp02, dp12) = (p0 - p2, p1 - p2);
+    let det = duv02.x * duv12.y - duv02.y * duv12.x;
+    if det.abs() < 1e-9 {
+        return (p1 - p0, p2 - p0);
+    }
+    (
+        (duv12.y * dp02 - duv02.y * dp12) / det,
+        (duv02.x * dp12 - duv12.x * dp02) / det,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::seeded_rng;
+    use rand::Rng;
+
+    // Rays aimed exactly at the edges and vertices between the triangles of a
+    // jittered grid must each hit at least one of them.
+    #[test]
+    fn shared_edges_are_watertight() {
+        let n = 8;
+        let mut rng = seeded_rng(7);
+        let mut positions = vec![];
+        for j in 0..=n {
+            for i in 0..=n {
+                let jitter = Vec3::new(rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3), 0.);
+                positions.push(Vec3::new(i as f32, j as f32, 0.) + jitter);
+            }
+        }
+        let vertex = |i: u32, j: u32| j * (n + 1) + i;
+        let mut indices = vec![];
+        for j in 0..n {
+            for i in 0..n {
+                let (v00, v10) = (vertex(i, j), vertex(i + 1, j));
+                let (v01, v11) = (vertex(i, j + 1), vertex(i + 1, j + 1));
+                indices.push([v00, v10, v11]);
+                indices.push([v00, v11, v01]);
+            }
+        }
+        let triangles = indices
+            .iter()
+            .map(|triangle| triangle.map(|i| positions[i as usize]))
+            .collect::<Vec<_>>();
+
+        for _ in 0..2000 {
+            // An edge away from the border of the grid, and a point on it.
+            let (i, j) = (rng.gen_range(1..n), rng.gen_range(1..n));
+            let a = positions[vertex(i, j) as usize];
+            let b = match rng.gen_range(0..3) {
+                0 => positions[vertex(i + 1, j) as usize],
+                1 => positions[vertex(i, j + 1) as usize],
+                _ => positions[vertex(i + 1, j + 1) as usize],
+            };
+            let target = if rng.gen_bool(0.1) {
+                a
+            } else {
+                a.lerp(b, rng.gen())
+            };
+
+            let side = if rng.gen_bool(0.5) { 1. } else { -1. };
+            let origin = Vec3::new(
+                rng.gen_range(-5.0..15.0),
+                rng.gen_range(-5.0..15.0),
+                side * rng.gen_range(0.5..20.0),
+            );
+            let ray = Ray {
+                origin,
+                direction: target - origin,
+                time: 0.,
+            };
+            assert!(
+                triangles
+                    .iter()
+                    .any(|&vertices| intersect(&ray, vertices).is_some()),
+                "ray from {origin} to {target} slipped through"
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    random::{mix, seeded_rng},
+    vector::random_vec3_in,
+};
+use glam::{IVec3, Vec3};
+use rand::seq::SliceRandom;
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise on the integer lattice, in roughly [-1, 1].
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm: [Vec<usize>; 3],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = seeded_rng(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| loop {
+                // Rejection keeps the directions uniform.
+                let v = random_vec3_in(&mut rng, -1.0..1.0);
+                if (1e-6..=1.).contains(&v.length_squared()) {
+                    break v.normalize();
+                }
+            })
+            .collect();
+        let mut permutation = || {
+            let mut perm = (0..POINT_COUNT).collect::<Vec<_>>();
+            perm.shuffle(&mut rng);
+            perm
+        };
+        let perm = [permutation(), permutation(), permutation()];
+        Self { gradients, perm }
+    }
+
+    pub fn noise(&self, p: Vec3) -> f32 {
+        let cell = p.floor();
+        let f = p - cell;
+        let i = cell.as_ivec3();
+        // Hermite smoothing hides the lattice.
+        let s = f * f * (3. - 2. * f);
+
+        let mut sum = 0.;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let corner = IVec3::new(di, dj, dk);
+                    let gradient = self.gradient(i + corner);
+                    let weight = corner.as_vec3() * s + (1 - corner).as_vec3() * (1. - s);
+                    sum += weight.x * weight.y * weight.z * gradient.dot(f - corner.as_vec3());
+                }
+            }
+        }
+        sum
+    }
+
+    // Octaves of noise at doubling frequency and halving weight.
+    pub fn fbm(&self, p: Vec3, octaves: u32) -> f32 {
+        self.octaves(p, octaves, |n| n)
+    }
+
+    // Like `fbm` but folding each octave's absolute value, which gives the
+    // creases that marble and smoke patterns build on.
+    pub fn turbulence(&self, p: Vec3, octaves: u32) -> f32 {
+        self.octaves(p, octaves, f32::abs)
+    }
+
+    fn octaves(&self, p: Vec3, octaves: u32, fold: impl Fn(f32) -> f32) -> f32 {
+        let mut sum = 0.;
+        let mut p = p;
+        let mut weight = 1.;
+        for _ in 0..octaves.max(1) {
+            sum += weight * fold(self.noise(p));
+            weight *= 0.5;
+            p *= 2.;
+        }
+        sum
+    }
+
+    fn gradient(&self, i: IVec3) -> Vec3 {
+        let index = |axis: usize, v: i32| self.perm[axis][(v & (POINT_COUNT as i32 - 1)) as usize];
+        self.gradients[index(0, i.x) ^ index(1, i.y) ^ index(2, i.z)]
+    }
+}
+
+// Cellular noise: the distance to the nearest of one random feature point
+// per lattice cell.
+#[derive(Clone, Copy, Debug)]
+pub struct Worley {
+    seed: u64,
+}
+
+impl Worley {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn distance(&self, p: Vec3) -> f32 {
+        let cell = p.floor().as_ivec3();
+        let mut nearest = f32::INFINITY;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let neighbour = cell + IVec3::new(dx, dy, dz);
+                    let feature = neighbour.as_vec3() + self.feature_offset(neighbour);
+                    nearest = nearest.min(feature.distance(p));
+                }
+            }
+        }
+        nearest
+    }
+
+    fn feature_offset(&self, cell: IVec3) -> Vec3 {
+        let mut hash = mix(self.seed);
+        for v in cell.to_array() {
+            hash = mix(hash ^ v as u32 as u64);
+        }
+        let unit = |bits: u64| (bits & 0xffff) as f32 / 65536.;
+        Vec3::new(unit(hash), unit(hash >> 16), unit(hash >> 32))
+    }
+}
+
+// Scalar patterns in [0, 1] built on the noise functions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pattern {
+    Perlin,
+    Fbm {
+        octaves: u32,
+    },
+    Turbulence {
+        octaves: u32,
+    },
+    Worley,
+    // Veins running along z, bent by `turbulence` times turbulence noise.
+    Marble {
+        octaves: u32,
+        turbulence: f32,
+    },
+    // Rings around the y axis, `rings` per unit radius, warped by fBm.
+    Wood {
+        rings: f32,
+        octaves: u32,
+        turbulence: f32,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Noise {
+    perlin: Perlin,
+    worley: Worley,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            worley: Worley::new(mix(seed)),
+        }
+    }
+
+    pub fn perlin(&self) -> &Perlin {
+        &self.perlin
+    }
+
+    pub fn worley(&self) -> &Worley {
+        &self.worley
+    }
+
+    pub fn evaluate(&self, pattern: Pattern, p: Vec3) -> f32 {
+        let value = match pattern {
+            Pattern::Perlin => 0.5 * (1. + self.perlin.noise(p)),
+            Pattern::Fbm { octaves } => 0.5 * (1. + self.perlin.fbm(p, octaves)),
+            Pattern::Turbulence { octaves } => self.perlin.turbulence(p, octaves),
+            Pattern::Worley => self.worley.distance(p),
+            Pattern::Marble {
+                octaves,
+                turbulence,
+            } => 0.5 * (1. + (p.z + turbulence * self.perlin.turbulence(p, octaves)).sin()),
+            Pattern::Wood {
+                rings,
+                octaves,
+                turbulence,
+            } => {
+                let radius = p.x.hypot(p.z) * rings;
+                (radius + turbulence * self.perlin.fbm(p, octaves)).rem_euclid(1.)
+            }
+        };
+        value.clamp(0., 1.)
+    }
+}
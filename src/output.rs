@@ -0,0 +1,252 @@
+use crate::{color, framebuffer::Framebuffer, tone_map::ToneMap};
+use glam::Vec3;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png8,
+    Png16,
+    Hdr,
+    Pfm,
+    Exr,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png8),
+            "hdr" => Some(Self::Hdr),
+            "pfm" => Some(Self::Pfm),
+            "exr" => Some(Self::Exr),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImageWriter {
+    pub format: Option<ImageFormat>,
+    pub embed_metadata: bool,
+    pub exposure: f32,
+    pub tone_map: ToneMap,
+}
+
+impl ImageWriter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn write(&self, image: &Framebuffer, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = self
+            .format
+            .or_else(|| ImageFormat::from_path(path))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("cannot infer image format from {}", path.display()),
+                )
+            })?;
+
+        match format {
+            ImageFormat::Ppm => self.write_ppm(image, path),
+            ImageFormat::Png8 => self.write_png(image, path, png::BitDepth::Eight),
+            ImageFormat::Png16 => self.write_png(image, path, png::BitDepth::Sixteen),
+            ImageFormat::Hdr => self.write_hdr(image, path),
+            ImageFormat::Pfm => self.write_pfm(image, path),
+            ImageFormat::Exr => self.write_exr(image, path),
+        }
+    }
+
+    pub fn display_color(&self, linear: Vec3) -> Vec3 {
+        self.tone_map
+            .apply(linear * self.exposure.exp2())
+            .map(color::linear_to_srgb)
+    }
+
+    fn write_ppm(&self, image: &Framebuffer, path: &Path) -> io::Result<()> {
+        let pixels = image
+            .pixels
+            .iter()
+            .map(|&c| color::to_ppm(self.display_color(c)))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        fs::write(
+            path,
+            format!("P3\n{} {}\n255\n{pixels}\n", image.width, image.height),
+        )
+    }
+
+    fn write_png(&self, image: &Framebuffer, path: &Path, depth: png::BitDepth) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, image.width, image.height);
+        encoder.set_color(match image.alpha {
+            Some(_) => png::ColorType::Rgba,
+            None => png::ColorType::Rgb,
+        });
+        encoder.set_depth(depth);
+        encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+
+        if self.embed_metadata {
+            for (key, value) in &image.metadata {
+                encoder.add_text_chunk(key.clone(), value.clone())?;
+            }
+        }
+
+        let mut data = vec![];
+        for (i, &c) in image.pixels.iter().enumerate() {
+            let alpha = image.alpha.as_ref().map(|alpha| alpha[i]);
+            let c = self.display_color(unpremultiply(c, alpha));
+            match (depth, alpha) {
+                (png::BitDepth::Sixteen, Some(a)) => data.extend(
+                    color::to_rgba16(c, a)
+                        .into_iter()
+                        .flat_map(u16::to_be_bytes),
+                ),
+                (png::BitDepth::Sixteen, None) => {
+                    data.extend(color::to_rgb16(c).into_iter().flat_map(u16::to_be_bytes))
+                }
+                (_, Some(a)) => data.extend(color::to_rgba8(c, a)),
+                (_, None) => data.extend(color::to_rgb8(c)),
+            }
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    fn write_hdr(&self, image: &Framebuffer, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(
+            file,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            image.height, image.width
+        )?;
+        for &c in &image.pixels {
+            file.write_all(&color::to_rgbe(c))?;
+        }
+        file.flush()
+    }
+
+    fn write_pfm(&self, image: &Framebuffer, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(file, "PF\n{} {}\n-1.0\n", image.width, image.height)?;
+        // PFM scanlines run bottom to top; the negative scale marks little endian.
+        for row in image.pixels.chunks(image.width as usize).rev() {
+            for c in row {
+                for channel in c.to_array() {
+                    file.write_all(&channel.to_le_bytes())?;
+                }
+            }
+        }
+        file.flush()
+    }
+
+    fn write_exr(&self, image: &Framebuffer, path: &Path) -> io::Result<()> {
+        const UINT: i32 = 0;
+        const FLOAT: i32 = 2;
+
+        let width = image.width as i32;
+        let height = image.height as i32;
+        let data_window = [0, 0, width - 1, height - 1]
+            .iter()
+            .flat_map(|v: &i32| v.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        // Every channel holds one 4-byte value per pixel.
+        let mut layers: Vec<(String, i32, Vec<[u8; 4]>)> = vec![];
+        if let Some(alpha) = &image.alpha {
+            let data = alpha.iter().map(|a| a.to_le_bytes()).collect();
+            layers.push(("A".to_string(), FLOAT, data));
+        }
+        let mut add_vec3 = |prefix: &str, names: [&str; 3], values: &[Vec3]| {
+            for (i, name) in names.iter().enumerate() {
+                let data = values.iter().map(|v| v[i].to_le_bytes()).collect();
+                layers.push((format!("{prefix}{name}"), FLOAT, data));
+            }
+        };
+        add_vec3("", ["R", "G", "B"], &image.pixels);
+        if let Some(aovs) = &image.aovs {
+            add_vec3("albedo.", ["R", "G", "B"], &aovs.albedo);
+            add_vec3("normal.", ["X", "Y", "Z"], &aovs.normal);
+            add_vec3("position.", ["X", "Y", "Z"], &aovs.position);
+            let depth = aovs.depth.iter().map(|d| d.to_le_bytes()).collect();
+            layers.push(("Z".to_string(), FLOAT, depth));
+            let ids = aovs.object_id.iter().map(|id| id.to_le_bytes()).collect();
+            layers.push(("objectId".to_string(), UINT, ids));
+        }
+        // Readers expect the channel list in alphabetical order.
+        layers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut channels = vec![];
+        for (name, kind, _) in &layers {
+            channels.extend_from_slice(name.as_bytes());
+            channels.push(0);
+            channels.extend_from_slice(&kind.to_le_bytes());
+            channels.extend_from_slice(&[0, 0, 0, 0]);
+            channels.extend_from_slice(&1_i32.to_le_bytes());
+            channels.extend_from_slice(&1_i32.to_le_bytes());
+        }
+        channels.push(0);
+
+        let mut out = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+        exr_attribute(&mut out, "channels", "chlist", &channels);
+        exr_attribute(&mut out, "compression", "compression", &[0]);
+        exr_attribute(&mut out, "dataWindow", "box2i", &data_window);
+        exr_attribute(&mut out, "displayWindow", "box2i", &data_window);
+        exr_attribute(&mut out, "lineOrder", "lineOrder", &[0]);
+        exr_attribute(&mut out, "pixelAspectRatio", "float", &1_f32.to_le_bytes());
+        exr_attribute(&mut out, "screenWindowCenter", "v2f", &[0; 8]);
+        exr_attribute(&mut out, "screenWindowWidth", "float", &1_f32.to_le_bytes());
+        out.push(0);
+
+        // Uncompressed scanline files store one scanline per chunk, each with
+        // its own entry in the offset table.
+        let width = image.width as usize;
+        let line_size = width * layers.len() * 4;
+        let chunk_size = 8 + line_size;
+        let table_end = out.len() + image.height as usize * 8;
+        for y in 0..image.height as usize {
+            out.extend_from_slice(&((table_end + y * chunk_size) as u64).to_le_bytes());
+        }
+
+        for y in 0..image.height as usize {
+            out.extend_from_slice(&(y as i32).to_le_bytes());
+            out.extend_from_slice(&(line_size as i32).to_le_bytes());
+            for (_, _, data) in &layers {
+                for value in &data[y * width..(y + 1) * width] {
+                    out.extend_from_slice(value);
+                }
+            }
+        }
+
+        fs::write(path, out)
+    }
+}
+
+// Framebuffer colors are premultiplied by alpha, which PNG does not expect.
+fn unpremultiply(color: Vec3, alpha: Option<f32>) -> Vec3 {
+    match alpha {
+        Some(a) if a > 0. => color / a,
+        _ => color,
+    }
+}
+
+fn exr_attribute(out: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    out.extend_from_slice(name.as_bytes());
+    out.push(0);
+    out.extend_from_slice(kind.as_bytes());
+    out.push(0);
+    out.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    out.extend_from_slice(value);
+}
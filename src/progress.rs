@@ -0,0 +1,161 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::{
+    fmt::Debug,
+    io,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderProgress {
+    pub pass: usize,
+    pub passes: usize,
+    pub tiles_finished: usize,
+    pub tiles: usize,
+    pub samples: u64,
+    pub elapsed: Duration,
+}
+
+impl RenderProgress {
+    pub fn fraction(&self) -> f32 {
+        let total = self.passes * self.tiles;
+        if total == 0 {
+            return 1.;
+        }
+        (self.pass.saturating_sub(1) * self.tiles + self.tiles_finished) as f32 / total as f32
+    }
+}
+
+// Tile events arrive from rayon worker threads, so observers only get `&self`
+// and need their own synchronisation.
+pub trait RenderObserver: Debug + Send + Sync {
+    fn started(&self, _progress: &RenderProgress) {}
+
+    fn tile_finished(&self, _progress: &RenderProgress) {}
+
+    fn pass_finished(&self, _progress: &RenderProgress) {}
+
+    fn finished(&self, _progress: &RenderProgress) {}
+
+    // A checkpoint or snapshot couldn't be written. The render carries on.
+    fn write_failed(&self, path: &Path, error: &io::Error) {
+        eprintln!("failed to write {}: {error}", path.display());
+    }
+}
+
+#[derive(Debug)]
+pub struct TerminalProgress {
+    bar: ProgressBar,
+}
+
+impl TerminalProgress {
+    pub fn new() -> Self {
+        let bar = ProgressBar::new(0);
+        bar.set_style(
+            ProgressStyle::with_template(
+                "{elapsed_precise} [{wide_bar}] {pos}/{len} tiles ({eta}) {msg}",
+            )
+            .unwrap(),
+        );
+        Self { bar }
+    }
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderObserver for TerminalProgress {
+    fn started(&self, progress: &RenderProgress) {
+        self.bar
+            .set_length((progress.passes * progress.tiles) as u64);
+        self.bar.reset();
+    }
+
+    fn tile_finished(&self, progress: &RenderProgress) {
+        self.bar
+            .set_position(((progress.pass - 1) * progress.tiles + progress.tiles_finished) as u64);
+    }
+
+    fn pass_finished(&self, progress: &RenderProgress) {
+        self.bar
+            .set_message(format!("pass {}/{}", progress.pass, progress.passes));
+    }
+
+    fn finished(&self, _progress: &RenderProgress) {
+        self.bar.finish();
+    }
+
+    fn write_failed(&self, path: &Path, error: &io::Error) {
+        self.bar
+            .println(format!("failed to write {}: {error}", path.display()));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentProgress;
+
+impl RenderObserver for SilentProgress {}
+
+// Prints plain lines to stderr for logs that can't render a progress bar,
+// at most once per `interval` for tile events.
+#[derive(Debug)]
+pub struct LogProgress {
+    pub interval: Duration,
+    last_line: Mutex<Option<Instant>>,
+}
+
+impl LogProgress {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_line: Mutex::new(None),
+        }
+    }
+
+    fn log(&self, event: &str, progress: &RenderProgress) {
+        *self.last_line.lock().unwrap() = Some(Instant::now());
+        Self::print(event, progress);
+    }
+
+    fn print(event: &str, progress: &RenderProgress) {
+        eprintln!(
+            "[{:>8.1}s] {event}: pass {}/{}, {}/{} tiles, {} samples ({:.1}%)",
+            progress.elapsed.as_secs_f32(),
+            progress.pass,
+            progress.passes,
+            progress.tiles_finished,
+            progress.tiles,
+            progress.samples,
+            progress.fraction() * 100.,
+        );
+    }
+}
+
+impl RenderObserver for LogProgress {
+    fn started(&self, progress: &RenderProgress) {
+        self.log("started", progress);
+    }
+
+    fn tile_finished(&self, progress: &RenderProgress) {
+        {
+            let mut last_line = self.last_line.lock().unwrap();
+            if last_line.is_some_and(|last| last.elapsed() < self.interval) {
+                return;
+            }
+            *last_line = Some(Instant::now());
+        }
+        Self::print("rendering", progress);
+    }
+
+    fn pass_finished(&self, progress: &RenderProgress) {
+        self.log("pass finished", progress);
+    }
+
+    fn finished(&self, progress: &RenderProgress) {
+        self.log("finished", progress);
+    }
+}
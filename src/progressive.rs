@@ -0,0 +1,21 @@
+use crate::output::ImageWriter;
+use std::{path::PathBuf, time::Duration};
+
+#[derive(Clone, Debug)]
+pub struct Progressive {
+    pub samples_per_pass: usize,
+    pub snapshot_path: PathBuf,
+    pub snapshot_interval: Duration,
+    pub writer: ImageWriter,
+}
+
+impl Progressive {
+    pub fn new(samples_per_pass: usize, snapshot_path: impl Into<PathBuf>) -> Self {
+        Self {
+            samples_per_pass,
+            snapshot_path: snapshot_path.into(),
+            snapshot_interval: Duration::ZERO,
+            writer: ImageWriter::new(),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    hittable_list::HittableList,
+    material::Material,
+    ray::Ray,
+    stats,
+};
+use glam::{Quat, Vec3};
+use std::ops::Range;
+
+// A parallelogram with a corner at `q` and edges `u` and `v`. Its surface
+// coordinates run from 0 to 1 along each edge, and the front face is on the
+// side `u × v` points to.
+#[derive(Clone, Debug)]
+pub struct Quad {
+    pub q: Vec3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Material,
+    pub object_id: u32,
+
+    normal: Vec3,
+    d: f32,
+    w: Vec3,
+    bbox: Aabb,
+}
+
+impl Quad {
+    pub fn new(q: Vec3, u: Vec3, v: Vec3, material: Material) -> Self {
+        let n = u.cross(v);
+        let normal = n.normalize_or_zero();
+        let bbox = Aabb::from_boxes(
+            Aabb::from_points(q, q + u + v),
+            &Aabb::from_points(q + u, q + v),
+        );
+        Self {
+            q,
+            u,
+            v,
+            material,
+            object_id: 0,
+            normal,
+            d: normal.dot(q),
+            w: n / n.dot(n),
+            bbox,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord<'_>> {
+        stats::count(|s| s.quad_tests += 1);
+        let denom = self.normal.dot(ray.direction);
+        // Rays parallel to the plane, and degenerate quads, never hit.
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(ray.origin)) / denom;
+        if !interval.contains(&t) {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let planar = p - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        if !(0. ..=1.).contains(&alpha) || !(0. ..=1.).contains(&beta) {
+            return None;
+        }
+
+        Some(HitRecord {
+            object_id: self.object_id,
+            dpdu: self.u,
+            dpdv: self.v,
+            ..HitRecord::with_front_face(p, t, self.normal, (alpha, beta), &self.material, ray)
+        })
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn set_object_id(&mut self, id: u32) {
+        self.object_id = id;
+    }
+}
+
+// The six outward facing sides of the box with opposite corners `a` and `b`,
+// turned by `rotation` about its center. Pass `Quat::IDENTITY` for an axis
+// aligned box.
+pub fn cuboid(a: Vec3, b: Vec3, rotation: Quat, material: Material) -> HittableList<Quad> {
+    let min = a.min(b);
+    let max = a.max(b);
+    let center = 0.5 * (min + max);
+    let size = max - min;
+    let dx = rotation * Vec3::new(size.x, 0., 0.);
+    let dy = rotation * Vec3::new(0., size.y, 0.);
+    let dz = rotation * Vec3::new(0., 0., size.z);
+    let lo = center + rotation * (min - center);
+
+    let mut sides = HittableList::new();
+    sides.add(Quad::new(lo + dz, dx, dy, material.clone())); // front
+    sides.add(Quad::new(lo + dx + dz, -dz, dy, material.clone())); // right
+    sides.add(Quad::new(lo + dx, -dx, dy, material.clone())); // back
+    sides.add(Quad::new(lo, dz, dy, material.clone())); // left
+    sides.add(Quad::new(lo + dy, dz, dx, material.clone())); // top
+    sides.add(Quad::new(lo, dx, dz, material)); // bottom
+    sides
+}
@@ -0,0 +1,21 @@
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+pub fn seeded_rng(seed: u64) -> Pcg32 {
+    Pcg32::seed_from_u64(mix(seed))
+}
+
+// Every camera sample gets its own stream derived from the render seed, the
+// pixel and the sample index, so results do not depend on which thread ends
+// up tracing which pixel.
+pub fn sample_rng(seed: u64, x: u32, y: u32, sample: u32) -> Pcg32 {
+    let pixel = ((y as u64) << 32) | x as u64;
+    Pcg32::seed_from_u64(mix(mix(mix(seed) ^ pixel) ^ sample as u64))
+}
+
+pub fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
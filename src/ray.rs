@@ -1,6 +1,18 @@
-use crate::hittable::Hittable;
+use crate::{
+    aov::SurfaceSample,
+    environment::Environment,
+    hittable::{HitRecord, Hittable},
+    sampler::Sampler,
+    stats,
+};
 use glam::Vec3;
 
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PathSample {
+    pub radiance: Vec3,
+    pub first_hit: Option<SurfaceSample>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Ray {
     pub origin: Vec3,
@@ -13,23 +25,143 @@ impl Ray {
         self.origin + time * self.direction
     }
 
-    pub fn color<T>(&self, world: &T, depth: usize) -> Vec3
+    pub fn color<T>(
+        &self,
+        world: &T,
+        environment: &Environment,
+        max_depth: usize,
+        spread: f32,
+        sampler: &mut dyn Sampler,
+    ) -> Vec3
     where
         T: Hittable + 'static + Sync,
     {
-        if depth == 0 {
-            return Vec3::ZERO;
-        }
+        self.trace(world, environment, max_depth, spread, sampler)
+            .radiance
+    }
+
+    // `spread` is the angle a pixel subtends at the camera; the ray's
+    // footprint grows by it with distance travelled along the whole path.
+    pub fn trace<T>(
+        &self,
+        world: &T,
+        environment: &Environment,
+        max_depth: usize,
+        spread: f32,
+        sampler: &mut dyn Sampler,
+    ) -> PathSample
+    where
+        T: Hittable + 'static + Sync,
+    {
+        let mut ray = self.clone();
+        let mut radiance = Vec3::ZERO;
+        let mut throughput = Vec3::ONE;
+        let mut first_hit = None;
+        let mut distance = 0.;
+        // Set when the last bounce also sampled the environment directly, so
+        // finding it again by scattering has to be weighted against that.
+        let mut scattering_pdf = None;
+
+        for depth in 0..max_depth {
+            stats::record_ray(depth);
+
+            let Some(mut hit_rec) = world.hit(&ray, 0.001..f32::INFINITY) else {
+                stats::count(|s| s.paths_escaped += 1);
+                let weight = scattering_pdf.map_or(1., |pdf| {
+                    power_heuristic(pdf, environment.pdf(ray.direction))
+                });
+                return PathSample {
+                    radiance: radiance + throughput * environment.radiance(ray.direction) * weight,
+                    first_hit,
+                };
+            };
+
+            distance += hit_rec.t * ray.direction.length();
+            hit_rec.cone_width = spread * distance;
+
+            if depth == 0 {
+                first_hit = Some(SurfaceSample {
+                    position: hit_rec.p,
+                    normal: hit_rec.normal,
+                    albedo: hit_rec.material.albedo(&hit_rec),
+                    depth: distance,
+                    object_id: hit_rec.object_id,
+                });
+            }
 
-        if let Some(hit_rec) = world.hit(self, 0.001..f32::INFINITY) {
-            if let Some(mat) = hit_rec.material.scatter(self, &hit_rec) {
-                return mat.attenuation * mat.scattered.color(world, depth - 1);
+            let material = &hit_rec.material;
+            radiance += throughput * material.emitted(&hit_rec);
+            if material.is_diffuse() {
+                radiance +=
+                    throughput * sample_environment(world, environment, &hit_rec, &ray, sampler);
             }
-            return Vec3::ZERO;
+
+            let Some(mat) = material.scatter(&ray, &hit_rec, sampler) else {
+                stats::count(|s| s.paths_absorbed += 1);
+                return PathSample {
+                    radiance,
+                    first_hit,
+                };
+            };
+            scattering_pdf = material
+                .is_diffuse()
+                .then(|| material.scattering_pdf(&hit_rec, mat.scattered.direction));
+            throughput *= mat.attenuation;
+            ray = mat.scattered;
+        }
+
+        stats::count(|s| s.paths_max_depth += 1);
+        PathSample {
+            radiance,
+            first_hit,
         }
+    }
+}
+
+// Light arriving straight from the environment at a diffuse hit, weighted
+// against the chance of scattering the same way.
+fn sample_environment<T>(
+    world: &T,
+    environment: &Environment,
+    hit_rec: &HitRecord,
+    ray: &Ray,
+    sampler: &mut dyn Sampler,
+) -> Vec3
+where
+    T: Hittable + 'static + Sync,
+{
+    let Some(light) = environment.sample(sampler) else {
+        return Vec3::ZERO;
+    };
+    let material = &hit_rec.material;
+    let scattering_pdf = material.scattering_pdf(hit_rec, light.direction);
+    if scattering_pdf == 0. || light.pdf == 0. {
+        return Vec3::ZERO;
+    }
+
+    stats::count(|s| s.shadow_rays += 1);
+    let shadow = Ray {
+        origin: hit_rec.p,
+        direction: light.direction,
+        time: ray.time,
+    };
+    if world.hit(&shadow, 0.001..f32::INFINITY).is_some() {
+        return Vec3::ZERO;
+    }
+
+    // For a Lambertian surface the BRDF times the cosine is the albedo times
+    // the scattering density.
+    material.albedo(hit_rec)
+        * scattering_pdf
+        * light.radiance
+        * power_heuristic(light.pdf, scattering_pdf)
+        / light.pdf
+}
 
-        let unit_direction = self.direction.normalize();
-        let a = 0.5 * (unit_direction.y + 1.0);
-        Vec3::ONE.lerp(Vec3::new(0.5, 0.7, 1.0), a)
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b == 0. {
+        return 0.;
     }
+    a / (a + b)
 }
@@ -0,0 +1,300 @@
+use crate::random;
+use glam::Vec2;
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+pub trait Sampler {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32);
+
+    fn get_1d(&mut self) -> f32;
+
+    fn get_2d(&mut self) -> Vec2;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SamplerKind {
+    #[default]
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    pub fn build(&self, seed: u64, samples_per_pixel: usize) -> Box<dyn Sampler> {
+        match self {
+            Self::Independent => Box::new(IndependentSampler::new(seed)),
+            Self::Stratified => Box::new(StratifiedSampler::new(seed, samples_per_pixel)),
+            Self::Halton => Box::new(HaltonSampler::new(seed)),
+            Self::Sobol => Box::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+pub struct IndependentSampler {
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: random::seeded_rng(seed),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.rng = random::sample_rng(self.seed, x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        self.rng.gen()
+    }
+
+    fn get_2d(&mut self) -> Vec2 {
+        Vec2::new(self.rng.gen(), self.rng.gen())
+    }
+}
+
+// Jittered sampling: every dimension is split into `samples_per_pixel`
+// strata (a near-square grid for 2D requests) and each sample index is sent
+// to its own stratum through a per-pixel, per-dimension permutation.
+pub struct StratifiedSampler {
+    seed: u64,
+    samples_per_pixel: u32,
+    grid: (u32, u32),
+    pixel: u64,
+    index: u32,
+    dimension: u64,
+    rng: Pcg32,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u64, samples_per_pixel: usize) -> Self {
+        let samples_per_pixel = (samples_per_pixel as u32).max(1);
+        let columns = (samples_per_pixel as f32).sqrt().ceil() as u32;
+        let rows = samples_per_pixel.div_ceil(columns);
+        Self {
+            seed,
+            samples_per_pixel,
+            grid: (columns, rows),
+            pixel: 0,
+            index: 0,
+            dimension: 0,
+            rng: random::seeded_rng(seed),
+        }
+    }
+
+    fn stratum(&mut self, count: u32) -> u32 {
+        let round = self.index / self.samples_per_pixel;
+        let hash = random::mix(
+            random::mix(self.seed ^ self.pixel) ^ (self.dimension << 32 | round as u64),
+        );
+        self.dimension += 1;
+        permute(self.index % self.samples_per_pixel, count, hash as u32)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = ((y as u64) << 32) | x as u64;
+        self.index = index;
+        self.dimension = 0;
+        self.rng = random::sample_rng(self.seed, x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let stratum = self.stratum(self.samples_per_pixel);
+        (stratum as f32 + self.rng.gen::<f32>()) / self.samples_per_pixel as f32
+    }
+
+    fn get_2d(&mut self) -> Vec2 {
+        let (columns, rows) = self.grid;
+        let stratum = self.stratum(columns * rows);
+        let cell = Vec2::new((stratum % columns) as f32, (stratum / columns) as f32);
+        let jitter = Vec2::new(self.rng.gen(), self.rng.gen());
+        (cell + jitter) / Vec2::new(columns as f32, rows as f32)
+    }
+}
+
+const PRIMES: [u32; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+// Halton points with every digit Owen-scrambled per pixel and dimension.
+// Dimensions past the prime table fall back to independent samples.
+pub struct HaltonSampler {
+    seed: u64,
+    pixel: u64,
+    index: u32,
+    dimension: usize,
+    rng: Pcg32,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: 0,
+            index: 0,
+            dimension: 0,
+            rng: random::seeded_rng(seed),
+        }
+    }
+
+    fn next(&mut self) -> f32 {
+        let Some(&base) = PRIMES.get(self.dimension) else {
+            return self.rng.gen();
+        };
+        let hash = random::mix(random::mix(self.seed ^ self.pixel) ^ self.dimension as u64);
+        self.dimension += 1;
+        scrambled_radical_inverse(base, self.index, hash)
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = ((y as u64) << 32) | x as u64;
+        self.index = index;
+        self.dimension = 0;
+        self.rng = random::sample_rng(self.seed, x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        self.next()
+    }
+
+    fn get_2d(&mut self) -> Vec2 {
+        Vec2::new(self.next(), self.next())
+    }
+}
+
+// Owen-scrambled Sobol points after Burley, "Practical Hash-based Owen
+// Scrambling" (2020): every request draws from the first two Sobol
+// dimensions, decorrelated by shuffling the sample index per dimension.
+pub struct SobolSampler {
+    seed: u64,
+    pixel: u64,
+    index: u32,
+    dimension: u64,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn scrambled(&mut self) -> (u32, u32) {
+        let hash = random::mix(random::mix(self.seed ^ self.pixel) ^ self.dimension);
+        self.dimension += 1;
+        let index = nested_uniform_scramble(self.index, hash as u32);
+        let x = nested_uniform_scramble(index.reverse_bits(), (hash >> 32) as u32);
+        let y = nested_uniform_scramble(sobol_second_dimension(index), random::mix(hash) as u32);
+        (x, y)
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = ((y as u64) << 32) | x as u64;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        to_unit_float(self.scrambled().0)
+    }
+
+    fn get_2d(&mut self) -> Vec2 {
+        let (x, y) = self.scrambled();
+        Vec2::new(to_unit_float(x), to_unit_float(y))
+    }
+}
+
+fn to_unit_float(bits: u32) -> f32 {
+    (bits >> 8) as f32 * (1. / (1 << 24) as f32)
+}
+
+fn scrambled_radical_inverse(base: u32, mut a: u32, hash: u64) -> f32 {
+    let inv_base = 1. / base as f32;
+    let mut inv_base_n = 1_f32;
+    let mut reversed = 0_u64;
+    while 1. - inv_base_n < 1. {
+        let next = a / base;
+        let digit = a - next * base;
+        let digit_hash = random::mix(hash ^ reversed) as u32;
+        reversed = reversed * base as u64 + permute(digit, base, digit_hash) as u64;
+        inv_base_n *= inv_base;
+        a = next;
+    }
+    (reversed as f32 * inv_base_n).min(1. - f32::EPSILON / 2.)
+}
+
+fn sobol_second_dimension(index: u32) -> u32 {
+    let mut direction = 1_u32 << 31;
+    let mut result = 0;
+    for bit in 0..32 {
+        if (index >> bit) & 1 == 1 {
+            result ^= direction;
+        }
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x
+}
+
+// Kensler, "Correlated Multi-Jittered Sampling" (2013): a hashed
+// permutation of 0..len that needs no table.
+fn permute(mut i: u32, len: u32, p: u32) -> u32 {
+    let mut w = len - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < len {
+            break;
+        }
+    }
+    (i.wrapping_add(p)) % len
+}
@@ -3,15 +3,17 @@ use crate::{
     hittable::{HitRecord, Hittable},
     material::Material,
     ray::Ray,
+    stats,
 };
 use glam::Vec3;
-use std::ops::Range;
+use std::{f32::consts::PI, ops::Range};
 
 #[derive(Clone, Debug)]
 pub struct Sphere {
     pub center: Ray,
     pub radius: f32,
     pub material: Material,
+    pub object_id: u32,
 
     bbox: Aabb,
 }
@@ -27,6 +29,7 @@ impl Sphere {
             },
             radius,
             material,
+            object_id: 0,
             bbox: Aabb::from_points(center - rvec, center + rvec),
         }
     }
@@ -44,13 +47,15 @@ impl Sphere {
             center,
             radius,
             material,
+            object_id: 0,
             bbox: Aabb::from_boxes(box1, &box2),
         }
     }
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, interval: Range<f32>) -> Option<HitRecord<'_>> {
+        stats::count(|s| s.sphere_tests += 1);
         let current_center = self.center.at(ray.time);
         let oc = current_center - ray.origin;
         let a = ray.direction.length_squared();
@@ -76,16 +81,42 @@ impl Hittable for Sphere {
         let p = ray.at(t);
         let normal = (p - current_center) / self.radius;
 
-        Some(HitRecord::with_front_face(
-            p,
-            t,
-            normal,
-            self.material.clone(),
-            ray,
-        ))
+        let uv = sphere_uv(normal);
+        let (dpdu, dpdv) = sphere_derivatives(normal, self.radius);
+
+        Some(HitRecord {
+            object_id: self.object_id,
+            dpdu,
+            dpdv,
+            ..HitRecord::with_front_face(p, t, normal, uv, &self.material, ray)
+        })
     }
 
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn set_object_id(&mut self, id: u32) {
+        self.object_id = id;
+    }
+}
+
+// Longitude around +Y starting from -X, and latitude from -Y, both mapped to
+// [0, 1] for a point on the unit sphere.
+fn sphere_uv(p: Vec3) -> (f32, f32) {
+    let theta = (-p.y).clamp(-1., 1.).acos();
+    let phi = (-p.z).atan2(p.x) + PI;
+    (phi / (2. * PI), theta / PI)
+}
+
+// Derivatives of the point on a sphere of `radius` with respect to the
+// coordinates from `sphere_uv`, which vanish at the poles.
+fn sphere_derivatives(n: Vec3, radius: f32) -> (Vec3, Vec3) {
+    let sin_theta = (n.x * n.x + n.z * n.z).sqrt();
+    let dpdu = 2. * PI * radius * Vec3::new(n.z, 0., -n.x);
+    if sin_theta < 1e-6 {
+        return (dpdu, Vec3::ZERO);
+    }
+    let dpdv = PI * radius * Vec3::new(-n.y * n.x / sin_theta, sin_theta, -n.y * n.z / sin_theta);
+    (dpdu, dpdv)
 }
@@ -0,0 +1,133 @@
+use std::{
+    cell::RefCell,
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct RenderStats {
+    pub camera_rays: u64,
+    pub rays_by_depth: Vec<u64>,
+    pub shadow_rays: u64,
+    pub aabb_tests: u64,
+    pub sphere_tests: u64,
+    pub quad_tests: u64,
+    pub triangle_tests: u64,
+    pub paths_escaped: u64,
+    pub paths_absorbed: u64,
+    pub paths_max_depth: u64,
+    pub build_time: Duration,
+    pub render_time: Duration,
+}
+
+impl RenderStats {
+    pub fn total_rays(&self) -> u64 {
+        self.rays_by_depth.iter().sum::<u64>() + self.shadow_rays
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.camera_rays += other.camera_rays;
+        if self.rays_by_depth.len() < other.rays_by_depth.len() {
+            self.rays_by_depth.resize(other.rays_by_depth.len(), 0);
+        }
+        for (total, count) in self.rays_by_depth.iter_mut().zip(&other.rays_by_depth) {
+            *total += count;
+        }
+        self.shadow_rays += other.shadow_rays;
+        self.aabb_tests += other.aabb_tests;
+        self.sphere_tests += other.sphere_tests;
+        self.quad_tests += other.quad_tests;
+        self.triangle_tests += other.triangle_tests;
+        self.paths_escaped += other.paths_escaped;
+        self.paths_absorbed += other.paths_absorbed;
+        self.paths_max_depth += other.paths_max_depth;
+        self.build_time += other.build_time;
+        self.render_time += other.render_time;
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"camera_rays\": {},", self.camera_rays);
+        let _ = writeln!(json, "  \"total_rays\": {},", self.total_rays());
+        let _ = writeln!(
+            json,
+            "  \"rays_by_depth\": [{}],",
+            self.rays_by_depth
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let _ = writeln!(json, "  \"shadow_rays\": {},", self.shadow_rays);
+        let _ = writeln!(json, "  \"aabb_tests\": {},", self.aabb_tests);
+        let _ = writeln!(json, "  \"primitive_tests\": {{");
+        let _ = writeln!(json, "    \"sphere\": {},", self.sphere_tests);
+        let _ = writeln!(json, "    \"quad\": {},", self.quad_tests);
+        let _ = writeln!(json, "    \"triangle\": {}", self.triangle_tests);
+        let _ = writeln!(json, "  }},");
+        let _ = writeln!(json, "  \"paths\": {{");
+        let _ = writeln!(json, "    \"escaped\": {},", self.paths_escaped);
+        let _ = writeln!(json, "    \"absorbed\": {},", self.paths_absorbed);
+        let _ = writeln!(json, "    \"max_depth\": {}", self.paths_max_depth);
+        let _ = writeln!(json, "  }},");
+        let _ = writeln!(
+            json,
+            "  \"build_seconds\": {},",
+            self.build_time.as_secs_f64()
+        );
+        let _ = writeln!(
+            json,
+            "  \"render_seconds\": {}",
+            self.render_time.as_secs_f64()
+        );
+        json.push_str("}\n");
+        json
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    // `image.png` gets `image.stats.json` next to it.
+    pub fn write_sidecar(&self, image_path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = image_path.as_ref().with_extension("stats.json");
+        self.write_json(&path)?;
+        Ok(path)
+    }
+}
+
+// Counters are kept per thread so the hot paths never contend; the camera
+// collects them around each tile, which runs start to finish on one thread.
+thread_local! {
+    static LOCAL: RefCell<RenderStats> = RefCell::default();
+}
+
+pub fn record(update: impl FnOnce(&mut RenderStats)) {
+    LOCAL.with_borrow_mut(update);
+}
+
+// Counting on the hot paths costs a thread-local borrow per ray and per box
+// or primitive test, so it only happens with the `stats` feature. Timings
+// are recorded either way.
+#[inline(always)]
+pub fn count(update: impl FnOnce(&mut RenderStats)) {
+    #[cfg(feature = "stats")]
+    record(update);
+    #[cfg(not(feature = "stats"))]
+    let _ = update;
+}
+
+pub fn take() -> RenderStats {
+    LOCAL.take()
+}
+
+pub(crate) fn record_ray(depth: usize) {
+    count(|stats| {
+        if stats.rays_by_depth.len() <= depth {
+            stats.rays_by_depth.resize(depth + 1, 0);
+        }
+        stats.rays_by_depth[depth] += 1;
+    });
+}
@@ -0,0 +1,299 @@
+use crate::{
+    color,
+    framebuffer::Framebuffer,
+    hittable::HitRecord,
+    input,
+    noise::{Noise, Pattern},
+    output::ImageFormat,
+};
+use glam::{Vec2, Vec3};
+use std::{io, path::Path, sync::Arc};
+
+// A color that varies over a surface, looked up by the hit's surface
+// coordinates `(u, v)` and its position `p`. Scalar parameters such as
+// roughness read the first channel.
+#[derive(Clone, Debug)]
+pub enum Texture {
+    Solid(Vec3),
+    // Alternates in cubes of side `scale` through space.
+    Checker {
+        scale: f32,
+        even: Arc<Texture>,
+        odd: Arc<Texture>,
+    },
+    // Alternates in `scale` sized squares of the surface's UV space.
+    UvChecker {
+        scale: Vec2,
+        even: Arc<Texture>,
+        odd: Arc<Texture>,
+    },
+    Image(Arc<ImageTexture>),
+    Noise(Arc<NoiseTexture>),
+}
+
+impl Texture {
+    pub fn checker(scale: f32, even: impl Into<Texture>, odd: impl Into<Texture>) -> Self {
+        Self::Checker {
+            scale,
+            even: Arc::new(even.into()),
+            odd: Arc::new(odd.into()),
+        }
+    }
+
+    pub fn uv_checker(scale: Vec2, even: impl Into<Texture>, odd: impl Into<Texture>) -> Self {
+        Self::UvChecker {
+            scale,
+            even: Arc::new(even.into()),
+            odd: Arc::new(odd.into()),
+        }
+    }
+
+    pub fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
+        self.filtered(u, v, p, Vec2::ZERO)
+    }
+
+    // Looks the texture up at a hit, filtering images over the area of the
+    // surface the ray's footprint covers.
+    pub fn evaluate(&self, hit_rec: &HitRecord) -> Vec3 {
+        self.filtered(hit_rec.u, hit_rec.v, hit_rec.p, hit_rec.uv_footprint())
+    }
+
+    fn filtered(&self, u: f32, v: f32, p: Vec3, footprint: Vec2) -> Vec3 {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Checker { scale, even, odd } => {
+                let cell = (p / *scale).floor();
+                if (cell.x + cell.y + cell.z) as i64 % 2 == 0 {
+                    even.filtered(u, v, p, footprint)
+                } else {
+                    odd.filtered(u, v, p, footprint)
+                }
+            }
+            Self::UvChecker { scale, even, odd } => {
+                let cell = (Vec2::new(u, v) / *scale).floor();
+                if (cell.x + cell.y) as i64 % 2 == 0 {
+                    even.filtered(u, v, p, footprint)
+                } else {
+                    odd.filtered(u, v, p, footprint)
+                }
+            }
+            Self::Image(image) => image.sample(Vec2::new(u, v), footprint),
+            Self::Noise(noise) => {
+                let t = noise.noise.evaluate(noise.pattern, p * noise.scale);
+                let low = noise.low.filtered(u, v, p, footprint);
+                let high = noise.high.filtered(u, v, p, footprint);
+                low.lerp(high, t)
+            }
+        }
+    }
+}
+
+impl From<Vec3> for Texture {
+    fn from(color: Vec3) -> Self {
+        Self::Solid(color)
+    }
+}
+
+impl From<f32> for Texture {
+    fn from(value: f32) -> Self {
+        Self::Solid(Vec3::splat(value))
+    }
+}
+
+impl From<ImageTexture> for Texture {
+    fn from(image: ImageTexture) -> Self {
+        Self::Image(Arc::new(image))
+    }
+}
+
+impl From<NoiseTexture> for Texture {
+    fn from(noise: NoiseTexture) -> Self {
+        Self::Noise(Arc::new(noise))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Bilinear,
+    // Bilinear lookups blended between the two nearest mip levels.
+    #[default]
+    Trilinear,
+}
+
+// How stored values map to linear ones. Color maps are usually sRGB encoded;
+// roughness and other data maps hold raw values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    Srgb,
+    Linear,
+}
+
+// An image stretched over the surface's UV square, with v running up from the
+// bottom row. Mip levels are built up front by halving the image until it is
+// a single pixel.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    pub wrap: Wrap,
+    pub filter: TextureFilter,
+    levels: Vec<Framebuffer>,
+}
+
+impl ImageTexture {
+    pub fn new(image: Framebuffer) -> Self {
+        let mut levels = vec![image];
+        while let Some(level) = levels.last().and_then(downsample) {
+            levels.push(level);
+        }
+        Self {
+            wrap: Wrap::default(),
+            filter: TextureFilter::default(),
+            levels,
+        }
+    }
+
+    // HDR files are always read as linear.
+    pub fn load(path: impl AsRef<Path>, color_space: ColorSpace) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut image = input::read_image(path)?;
+        if color_space == ColorSpace::Srgb && ImageFormat::from_path(path) != Some(ImageFormat::Hdr)
+        {
+            for pixel in &mut image.pixels {
+                *pixel = pixel.map(color::srgb_to_linear);
+            }
+        }
+        Ok(Self::new(image))
+    }
+
+    pub fn image(&self) -> &Framebuffer {
+        &self.levels[0]
+    }
+
+    pub fn value(&self, u: f32, v: f32) -> Vec3 {
+        self.sample(Vec2::new(u, v), Vec2::ZERO)
+    }
+
+    // `footprint` is the extent in UV space the lookup should average over.
+    pub fn sample(&self, uv: Vec2, footprint: Vec2) -> Vec3 {
+        let image = self.image();
+        if image.width == 0 || image.height == 0 {
+            return Vec3::ZERO;
+        }
+        match self.filter {
+            TextureFilter::Nearest => self.nearest(0, uv),
+            TextureFilter::Bilinear => self.bilinear(0, uv),
+            TextureFilter::Trilinear => {
+                let texels = (footprint * Vec2::new(image.width as f32, image.height as f32))
+                    .max_element()
+                    .max(1.);
+                let lod = texels.log2().min((self.levels.len() - 1) as f32);
+                let level = lod.floor() as usize;
+                let blend = lod - level as f32;
+                if blend == 0. {
+                    return self.bilinear(level, uv);
+                }
+                self.bilinear(level, uv)
+                    .lerp(self.bilinear(level + 1, uv), blend)
+            }
+        }
+    }
+
+    fn nearest(&self, level: usize, uv: Vec2) -> Vec3 {
+        let image = &self.levels[level];
+        let x = (uv.x * image.width as f32).floor() as i64;
+        let y = ((1. - uv.y) * image.height as f32).floor() as i64;
+        image.pixel(
+            self.wrap.apply(x, image.width),
+            self.wrap.apply(y, image.height),
+        )
+    }
+
+    fn bilinear(&self, level: usize, uv: Vec2) -> Vec3 {
+        let image = &self.levels[level];
+        let x = uv.x * image.width as f32 - 0.5;
+        let y = (1. - uv.y) * image.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let texel = |dx: i64, dy: i64| {
+            image.pixel(
+                self.wrap.apply(x0 as i64 + dx, image.width),
+                self.wrap.apply(y0 as i64 + dy, image.height),
+            )
+        };
+        let top = texel(0, 0).lerp(texel(1, 0), fx);
+        let bottom = texel(0, 1).lerp(texel(1, 1), fx);
+        top.lerp(bottom, fy)
+    }
+}
+
+// Blends from `low` to `high` by a noise pattern evaluated at the hit
+// position times `scale`. The same seed always gives the same pattern.
+#[derive(Clone, Debug)]
+pub struct NoiseTexture {
+    pub pattern: Pattern,
+    pub scale: f32,
+    pub low: Texture,
+    pub high: Texture,
+    noise: Noise,
+}
+
+impl NoiseTexture {
+    pub fn new(pattern: Pattern, seed: u64) -> Self {
+        Self {
+            pattern,
+            scale: 1.,
+            low: Vec3::ZERO.into(),
+            high: Vec3::ONE.into(),
+            noise: Noise::new(seed),
+        }
+    }
+}
+
+impl Wrap {
+    fn apply(self, i: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let i = match self {
+            Self::Repeat => i.rem_euclid(size),
+            Self::Clamp => i.clamp(0, size - 1),
+            Self::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size {
+                    i
+                } else {
+                    2 * size - 1 - i
+                }
+            }
+        };
+        i as u32
+    }
+}
+
+// Averages 2x2 blocks, repeating the last row or column of odd sizes.
+fn downsample(image: &Framebuffer) -> Option<Framebuffer> {
+    if image.width <= 1 && image.height <= 1 {
+        return None;
+    }
+    let width = image.width.div_ceil(2);
+    let height = image.height.div_ceil(2);
+    let mut level = Framebuffer::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = Vec3::ZERO;
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let sx = (2 * x + dx).min(image.width - 1);
+                let sy = (2 * y + dy).min(image.height - 1);
+                sum += image.pixel(sx, sy);
+            }
+            level.set_pixel(x, y, sum / 4.);
+        }
+    }
+    Some(level)
+}
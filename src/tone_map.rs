@@ -0,0 +1,93 @@
+use glam::{Mat3, Vec3};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ToneMap {
+    #[default]
+    Clamp,
+    Reinhard,
+    ExtendedReinhard {
+        white: f32,
+    },
+    Aces,
+    Agx,
+}
+
+impl ToneMap {
+    pub fn apply(&self, color: Vec3) -> Vec3 {
+        let color = color.max(Vec3::ZERO);
+        let mapped = match self {
+            Self::Clamp => color,
+            Self::Reinhard => scale_luminance(color, |l| l / (1. + l)),
+            Self::ExtendedReinhard { white } => {
+                let white2 = white * white;
+                scale_luminance(color, |l| l * (1. + l / white2) / (1. + l))
+            }
+            Self::Aces => aces(color),
+            Self::Agx => agx(color),
+        };
+        mapped.clamp(Vec3::ZERO, Vec3::ONE)
+    }
+}
+
+pub fn luminance(color: Vec3) -> f32 {
+    color.dot(Vec3::new(0.2126, 0.7152, 0.0722))
+}
+
+fn scale_luminance(color: Vec3, map: impl Fn(f32) -> f32) -> Vec3 {
+    let l = luminance(color);
+    if l <= 0. {
+        return Vec3::ZERO;
+    }
+    color * (map(l) / l)
+}
+
+// Stephen Hill's fit of the ACES reference rendering and output transforms.
+fn aces(color: Vec3) -> Vec3 {
+    let input = Mat3::from_cols_array_2d(&[
+        [0.59719, 0.35458, 0.04823],
+        [0.07600, 0.90834, 0.01566],
+        [0.02840, 0.13383, 0.83777],
+    ])
+    .transpose();
+    let output = Mat3::from_cols_array_2d(&[
+        [1.60475, -0.53108, -0.07367],
+        [-0.10208, 1.10813, -0.00605],
+        [-0.00327, -0.07276, 1.07602],
+    ])
+    .transpose();
+
+    let v = input * color;
+    let rrt_odt = (v * (v + 0.0245786) - 0.000090537) / (v * (0.983729 * v + 0.432951) + 0.238081);
+    output * rrt_odt
+}
+
+// Minimal AgX with the default look, following Benjamin Wrensch's polynomial fit.
+fn agx(color: Vec3) -> Vec3 {
+    const MIN_EV: f32 = -12.47393;
+    const MAX_EV: f32 = 4.026069;
+
+    let inset = Mat3::from_cols_array_2d(&[
+        [0.8424791, 0.04232824, 0.04237565],
+        [0.0784336, 0.8784686, 0.0784336],
+        [0.07922375, 0.07916613, 0.879143],
+    ]);
+    let outset = Mat3::from_cols_array_2d(&[
+        [1.196879, -0.05289685, -0.05297164],
+        [-0.09802088, 1.151903, -0.09804345],
+        [-0.09902974, -0.09896118, 1.151074],
+    ]);
+
+    let v = (inset * color)
+        .max(Vec3::splat(1e-10))
+        .map(f32::log2)
+        .clamp(Vec3::splat(MIN_EV), Vec3::splat(MAX_EV));
+    let v = (v - MIN_EV) / (MAX_EV - MIN_EV);
+
+    let v2 = v * v;
+    let v4 = v2 * v2;
+    let curve =
+        15.5 * v4 * v2 - 40.14 * v4 * v + 31.96 * v4 - 6.868 * v2 * v + 0.4298 * v2 + 0.1191 * v
+            - 0.00232;
+
+    (outset * curve).max(Vec3::ZERO).powf(2.2)
+}
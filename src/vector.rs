@@ -1,14 +1,15 @@
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 use rand::Rng;
-use std::ops::Range;
+use std::{
+    f32::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    ops::Range,
+};
 
-pub fn random_vec3() -> Vec3 {
-    let mut rng = rand::thread_rng();
+pub fn random_vec3(rng: &mut impl Rng) -> Vec3 {
     Vec3::new(rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>())
 }
 
-pub fn random_vec3_in(interval: Range<f32>) -> Vec3 {
-    let mut rng = rand::thread_rng();
+pub fn random_vec3_in(rng: &mut impl Rng, interval: Range<f32>) -> Vec3 {
     Vec3::new(
         rng.gen_range(interval.start..interval.end),
         rng.gen_range(interval.start..interval.end),
@@ -16,21 +17,24 @@ pub fn random_vec3_in(interval: Range<f32>) -> Vec3 {
     )
 }
 
-pub fn random_unit_vector() -> Vec3 {
-    loop {
-        let p = random_vec3_in(-1.0..1.0);
-        if (1e-160_f32..1.0).contains(&p.length_squared()) {
-            break p.normalize();
-        }
-    }
+pub fn sample_unit_vector(u: Vec2) -> Vec3 {
+    let z = 1. - 2. * u.x;
+    let r = (1. - z * z).max(0.).sqrt();
+    let phi = 2. * PI * u.y;
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
 }
 
-pub fn random_in_unit_disk() -> Vec3 {
-    let mut rng = rand::thread_rng();
-    loop {
-        let p = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.);
-        if p.length_squared() < 1. {
-            break p;
-        }
+// Shirley-Chiu concentric mapping, which keeps stratified samples stratified.
+pub fn sample_unit_disk(u: Vec2) -> Vec3 {
+    let offset = 2. * u - Vec2::ONE;
+    if offset == Vec2::ZERO {
+        return Vec3::ZERO;
     }
+
+    let (r, theta) = if offset.x.abs() > offset.y.abs() {
+        (offset.x, FRAC_PI_4 * (offset.y / offset.x))
+    } else {
+        (offset.y, FRAC_PI_2 - FRAC_PI_4 * (offset.x / offset.y))
+    };
+    Vec3::new(r * theta.cos(), r * theta.sin(), 0.)
 }